@@ -27,16 +27,23 @@ fn main() {
             .and_then(|s| s.strip_suffix(".rs"))
         {
             day_numbers.push(day_num.to_owned());
-            mod_code.push_str(&format!(
-                indoc! {r#"
-                    #[path = "day_{0}.rs"]
-                    pub mod day_{0};
-                "# },
-                day_num
-            ));
         }
     }
 
+    // `read_dir` order is platform dependent, sort it so the generated file
+    // doesn't change between builds.
+    day_numbers.sort();
+
+    for day_num in &day_numbers {
+        mod_code.push_str(&format!(
+            indoc! {r#"
+                #[path = "day_{0}.rs"]
+                pub mod day_{0};
+            "# },
+            day_num
+        ));
+    }
+
     mod_code.push_str("\n// Generate static day solution map\n");
     mod_code.push_str(&format!("register_days![{}];\n", day_numbers.join(", ")));
 
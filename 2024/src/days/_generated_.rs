@@ -1,18 +1,18 @@
 // DO NOT MODIFY!
 //
 // Auto-generated module exports
-#[path = "day_03.rs"]
-pub mod day_03;
-#[path = "day_06.rs"]
-pub mod day_06;
-#[path = "day_02.rs"]
-pub mod day_02;
-#[path = "day_05.rs"]
-pub mod day_05;
 #[path = "day_01.rs"]
 pub mod day_01;
+#[path = "day_02.rs"]
+pub mod day_02;
+#[path = "day_03.rs"]
+pub mod day_03;
 #[path = "day_04.rs"]
 pub mod day_04;
+#[path = "day_05.rs"]
+pub mod day_05;
+#[path = "day_06.rs"]
+pub mod day_06;
 
 // Generate static day solution map
-register_days![03, 06, 02, 05, 01, 04];
+register_days![01, 02, 03, 04, 05, 06];
@@ -1,13 +1,16 @@
-use std::collections::HashMap;
 use std::fmt::Display;
 
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng};
+
+use aoc2024::utils::{counter::Counter, parse};
+
 pub fn part_1(input: &str) -> Box<dyn Display> {
     let (mut first_list, mut second_list) = parse(input);
     first_list.sort();
     second_list.sort();
 
     Box::new(
-        std::iter::zip(first_list.into_iter(), second_list.into_iter())
+        std::iter::zip(first_list, second_list)
             .map(|(a, b)| a.abs_diff(b))
             .sum::<u64>(),
     )
@@ -15,40 +18,36 @@ pub fn part_1(input: &str) -> Box<dyn Display> {
 
 pub fn part_2(input: &str) -> Box<dyn Display> {
     let (first, second) = parse(input);
-    let mut occurency_map = HashMap::<u64, u64>::new();
-
-    for v in second {
-        occurency_map
-            .entry(v)
-            .and_modify(|o| {
-                *o += 1;
-            })
-            .or_insert(1);
-    }
+    let occurrences: Counter<u64> = second.into_iter().collect();
 
     Box::new(
         first
             .into_iter()
-            .map(|k| match occurency_map.get(&k) {
-                Some(count) => count * k,
-                None => 0,
-            })
+            .map(|k| occurrences.count(&k) as u64 * k)
             .sum::<u64>(),
     )
 }
 
 fn parse(input: &str) -> (Vec<u64>, Vec<u64>) {
-    let (mut first, mut second) = (vec![], vec![]);
+    let [first, second] = parse::columns(input).unwrap();
+    (first, second)
+}
 
-    for line in input.lines().map(str::trim) {
-        if line.is_empty() {
-            continue;
-        }
-        let mut nums = line.split_whitespace().map(|n| n.parse().unwrap());
-        first.push(nums.next().unwrap());
-        second.push(nums.next().unwrap());
+/// Generates `size` lines of location id pairs.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let first: Vec<u64> = (0..size).map(|_| rng.random_range(10000..100000)).collect();
+    let mut input = String::new();
+
+    for a in &first {
+        // Reuse some ids from the first list, otherwise the similarity score
+        // is almost always zero.
+        let b = match rng.random_bool(0.3) {
+            true => *first.choose(rng).unwrap(),
+            false => rng.random_range(10000..100000),
+        };
+        input.push_str(&format!("{a}   {b}\n"));
     }
-    (first, second)
+    input
 }
 
 #[cfg(test)]
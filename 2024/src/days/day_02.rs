@@ -1,5 +1,10 @@
 use std::fmt::Display;
 
+use itertools::Itertools;
+use rand::{rngs::StdRng, Rng};
+
+use aoc2024::utils::parse;
+
 fn is_safe(report: &[u64]) -> bool {
     if report.len() <= 1 {
         return true;
@@ -48,14 +53,31 @@ pub fn part_2(input: &str) -> Box<dyn Display> {
 }
 
 fn parse(input: &str) -> Vec<Vec<u64>> {
+    parse::parse_lines(input, parse::ints).unwrap()
+}
+
+/// Generates `size` reports of 5 to 8 levels. Most of them are monotonic with
+/// small deltas, with the occasional bad level mixed in.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut input = String::new();
+
+    for _ in 0..size {
+        let len = rng.random_range(5..=8);
+        let sign = if rng.random_bool(0.5) { 1 } else { -1 };
+        let mut level: i64 = rng.random_range(10..=90);
+        let mut report = Vec::with_capacity(len);
+
+        for _ in 0..len {
+            report.push(level);
+            let delta = match rng.random_bool(0.1) {
+                true => rng.random_range(-5..=5),
+                false => sign * rng.random_range(1..=3),
+            };
+            level = (level + delta).max(1);
+        }
+        input.push_str(&format!("{}\n", report.iter().join(" ")));
+    }
     input
-        .lines()
-        .map(|l| {
-            l.split_whitespace()
-                .filter_map(|s| s.parse().ok())
-                .collect()
-        })
-        .collect()
 }
 
 #[cfg(test)]
@@ -1,110 +1,195 @@
-use std::{fmt::Display, str::FromStr};
+use std::fmt::{Display, Write};
 
-#[derive(Debug)]
-struct MulOperation(i64, i64);
+use memchr::{memchr, memchr2, memchr3};
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng};
 
-impl MulOperation {
-    pub fn run(&self) -> i64 {
-        self.0.checked_mul(self.1).unwrap()
+/// State of the machine the instructions of the memory run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Machine {
+    sum: i64,
+    enabled: bool,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            sum: 0,
+            enabled: true,
+        }
     }
 }
 
-#[derive(Debug)]
-struct ParseMulOperationError;
+/// Instruction written `name(a,b,...)` with `arity` arguments of one to three
+/// digits, changing the machine state with `effect`.
+struct Instruction {
+    name: &'static str,
+    arity: usize,
+    effect: fn(&mut Machine, &[i64]),
+}
 
-impl FromStr for MulOperation {
-    type Err = ParseMulOperationError;
+/// Instruction recognized in the memory, as listed by the trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Step<'a> {
+    offset: usize,
+    text: &'a str,
+    /// Whether instructions were enabled when it ran.
+    enabled: bool,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (a, b) = s
-            .strip_prefix("mul(")
-            .and_then(|s| s.strip_suffix(")"))
-            .and_then(|s| s.split_once(","))
-            .ok_or(ParseMulOperationError)?;
+#[derive(Default)]
+struct Interpreter {
+    instructions: Vec<Instruction>,
+    /// Distinct first bytes of the instruction names, to skip straight to
+    /// the next possible instruction.
+    first_bytes: Vec<u8>,
+}
 
-        let a = a.parse::<i64>().map_err(|_| ParseMulOperationError)?;
-        let b = b.parse::<i64>().map_err(|_| ParseMulOperationError)?;
-        Ok(Self(a, b))
+impl Interpreter {
+    fn register(
+        mut self,
+        name: &'static str,
+        arity: usize,
+        effect: fn(&mut Machine, &[i64]),
+    ) -> Self {
+        let first = name.as_bytes()[0];
+        if !self.first_bytes.contains(&first) {
+            self.first_bytes.push(first);
+        }
+        self.instructions.push(Instruction {
+            name,
+            arity,
+            effect,
+        });
+        self
     }
-}
 
-#[derive(Debug)]
-enum Operation {
-    Mul(MulOperation),
-    Do,
-    Dont,
-}
+    /// Offset of the next byte of `s` that may start an instruction.
+    fn next_candidate(&self, s: &[u8]) -> Option<usize> {
+        match *self.first_bytes.as_slice() {
+            [] => None,
+            [a] => memchr(a, s),
+            [a, b] => memchr2(a, b, s),
+            [a, b, c] => memchr3(a, b, c, s),
+            ref many => s.iter().position(|c| many.contains(c)),
+        }
+    }
 
-#[derive(Debug)]
-struct ParseOperationError;
+    /// Arguments of a call to an instruction with the given arity at the
+    /// start of `s`, and the length of the call up to its closing `)`.
+    fn parse_call(s: &[u8], arity: usize) -> Option<(Vec<i64>, usize)> {
+        let mut rest = s.strip_prefix(b"(")?;
+        let mut args = Vec::with_capacity(arity);
 
-impl FromStr for Operation {
-    type Err = ParseOperationError;
+        for k in 0..arity {
+            if k > 0 {
+                rest = rest.strip_prefix(b",")?;
+            }
+            let len = rest.iter().take(4).take_while(|c| c.is_ascii_digit()).count();
+            if !(1..=3).contains(&len) {
+                return None;
+            }
+            args.push(rest[..len].iter().fold(0, |n, &d| n * 10 + (d - b'0') as i64));
+            rest = &rest[len..];
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use Operation::*;
+        rest = rest.strip_prefix(b")")?;
+        Some((args, s.len() - rest.len()))
+    }
 
-        Ok(match s {
-            "do()" => Do,
-            "don't()" => Dont,
-            s => Mul(s.parse().map_err(|_| ParseOperationError)?),
+    /// Instruction called at the start of `s`, with its arguments and the
+    /// length of the call.
+    fn decode(&self, s: &[u8]) -> Option<(&Instruction, Vec<i64>, usize)> {
+        self.instructions.iter().find_map(|inst| {
+            let rest = s.strip_prefix(inst.name.as_bytes())?;
+            let (args, len) = Self::parse_call(rest, inst.arity)?;
+            Some((inst, args, inst.name.len() + len))
         })
     }
+
+    /// Runs every instruction found in the memory, skipping anything else,
+    /// and calls `on_step` before each one. The memory is scanned once and
+    /// doesn't need to be valid UTF-8.
+    fn run<'a>(&self, memory: &'a [u8], mut on_step: impl FnMut(Step<'a>)) -> Machine {
+        let mut machine = Machine::default();
+        let mut pos = 0;
+
+        while let Some(offset) = self.next_candidate(&memory[pos..]) {
+            pos += offset;
+            match self.decode(&memory[pos..]) {
+                Some((inst, args, len)) => {
+                    // A call is made of the ASCII name, digits and punctuation.
+                    let text = std::str::from_utf8(&memory[pos..pos + len]).unwrap();
+                    on_step(Step {
+                        offset: pos,
+                        text,
+                        enabled: machine.enabled,
+                    });
+                    (inst.effect)(&mut machine, &args);
+                    pos += len;
+                }
+                None => pos += 1,
+            }
+        }
+        machine
+    }
 }
 
-fn find_closest(s: &str, pats: &[&str]) -> Option<usize> {
-    pats.iter()
-        .filter_map(|p| s.find(p))
-        .fold(None, |acc, i| Some(acc.unwrap_or(usize::MAX).min(i)))
+fn mul(m: &mut Machine, args: &[i64]) {
+    if m.enabled {
+        m.sum += args[0] * args[1];
+    }
 }
 
-pub fn part_1(input: &str) -> Box<dyn Display> {
-    let mut input = input;
-    let mut sum = 0;
+fn conditional_interpreter() -> Interpreter {
+    Interpreter::default()
+        .register("mul", 2, mul)
+        .register("do", 0, |m, _| m.enabled = true)
+        .register("don't", 0, |m, _| m.enabled = false)
+}
 
-    while let Some(start) = input.find("mul(") {
-        if let Some(end) = input[start..].find(')') {
-            let end = end + start;
+pub fn part_1(input: &[u8]) -> Box<dyn Display> {
+    let interpreter = Interpreter::default().register("mul", 2, mul);
+    Box::new(interpreter.run(input, |_| ()).sum)
+}
 
-            sum += &input[start..=end]
-                .parse::<MulOperation>()
-                .map_or(0, |m| m.run());
+pub fn part_2(input: &[u8]) -> Box<dyn Display> {
+    Box::new(conditional_interpreter().run(input, |_| ()).sum)
+}
 
-            input = &input[start + 3..];
-        } else {
-            break;
-        }
-    }
-    Box::new(sum)
+/// Trace of every instruction of part 2, with its offset and whether
+/// instructions were enabled when it ran.
+pub fn explain(input: &[u8]) -> String {
+    let mut trace = String::new();
+    conditional_interpreter().run(input, |step| {
+        let state = match step.enabled {
+            true => "enabled",
+            false => "disabled",
+        };
+        let _ = writeln!(trace, "{:>8}: {} ({state})", step.offset, step.text);
+    });
+    trace
 }
 
-pub fn part_2(input: &str) -> Box<dyn Display> {
-    let mut input = input;
-    let mut sum = 0;
-    let mut enable = true;
-
-    while let Some(start) = find_closest(input, &["mul", "do", "don't"]) {
-        if let Some(end) = input[start..].find(')') {
-            let end = end + start;
-
-            use Operation::*;
-            match &input[start..=end].parse::<Operation>().ok() {
-                Some(Do) => enable = true,
-                Some(Dont) => enable = false,
-                Some(Mul(m)) => {
-                    if enable {
-                        sum += m.run()
-                    }
-                }
-                _ => (),
-            }
+/// Generates roughly `size` bytes of corrupted memory, mixing valid and
+/// malformed instructions with noise.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    const NOISE: &[u8] = b"!@#$%^&*()[]{}<>,;:'?+-_ /whyselectfromhowwhat";
+    let mut input = String::new();
 
-            input = &input[start + 3..];
-        } else {
-            break;
+    while input.len() < size {
+        let (a, b) = (rng.random_range(1..1000), rng.random_range(1..1000));
+        match rng.random_range(0..10) {
+            0..=1 => input.push_str(&format!("mul({a},{b})")),
+            2 => input.push_str(&format!("mul({a},{b}]")),
+            3 => input.push_str(&format!("mul ( {a},{b} )")),
+            4 => input.push_str("do()"),
+            5 => input.push_str("don't()"),
+            _ => (0..rng.random_range(1..8))
+                .for_each(|_| input.push(*NOISE.choose(rng).unwrap() as char)),
         }
     }
-    Box::new(sum)
+    input.push('\n');
+    input
 }
 
 #[cfg(test)]
@@ -118,13 +203,65 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let res = part_1(TEST_INPUT);
+        let res = part_1(TEST_INPUT.as_bytes());
         assert_eq!(&res.to_string(), "161");
     }
 
     #[test]
     fn test_part_2() {
-        let res = part_2(TEST_INPUT);
+        let res = part_2(TEST_INPUT.as_bytes());
         assert_eq!(&res.to_string(), "48");
     }
+
+    #[test]
+    fn test_interpreter() {
+        let interpreter = conditional_interpreter().register("add", 3, |m, args| {
+            m.sum += args.iter().sum::<i64>();
+        });
+
+        let mut steps = vec![];
+        let memory = b"add(1,2,3)don't()mul(4,5)add(1,2)mul(1234,1)";
+        let machine = interpreter.run(memory, |s| {
+            steps.push(s)
+        });
+        assert_eq!(machine.sum, 6);
+        assert!(!machine.enabled);
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    offset: 0,
+                    text: "add(1,2,3)",
+                    enabled: true,
+                },
+                Step {
+                    offset: 10,
+                    text: "don't()",
+                    enabled: true,
+                },
+                Step {
+                    offset: 17,
+                    text: "mul(4,5)",
+                    enabled: false,
+                },
+            ]
+        );
+
+        let trace = explain(TEST_INPUT.as_bytes());
+        assert_eq!(trace.lines().count(), 6);
+        assert!(trace.contains("don't() (enabled)"));
+        assert!(trace.contains("mul(5,5) (disabled)"));
+    }
+
+    #[test]
+    fn test_arbitrary_bytes() {
+        // Multi-byte characters right after a name and invalid UTF-8.
+        let memory = "domul(2,3)\u{e9}mul\u{e9}(1,1)don\u{2019}t()mul(4,5)".as_bytes();
+        assert_eq!(&part_2(memory).to_string(), "26");
+
+        let memory = [b"\xff\xfemul(3,3)\xc3".as_slice(), b"don't()\x80mul(1,2)"].concat();
+        assert_eq!(&part_1(&memory).to_string(), "11");
+        assert_eq!(&part_2(&memory).to_string(), "9");
+        assert_eq!(explain(&memory).lines().count(), 3);
+    }
 }
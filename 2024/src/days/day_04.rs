@@ -1,91 +1,53 @@
-use std::{fmt::Display, ops::Index};
+use std::fmt::Display;
 
-type Point = (usize, usize); // Point = (i, j)
-type Direction = (isize, isize); // Direction = (delta i, delta j)
+use rand::{rngs::StdRng, seq::IndexedRandom};
 
-#[derive(Debug)]
-struct CharGrid {
-    data: Vec<char>,
-    cols: usize,
-}
-
-impl CharGrid {
-    #[inline]
-    pub fn cols(&self) -> usize {
-        self.cols
-    }
-
-    #[inline]
-    pub fn rows(&self) -> usize {
-        self.data.len() / self.cols
-    }
-}
+use aoc2024::utils::{
+    geometry::{Dir8, Point},
+    grid::Grid,
+};
 
-impl Index<usize> for CharGrid {
-    type Output = [char];
-
-    fn index(&self, row: usize) -> &Self::Output {
-        let start = row * self.cols;
-        let end = start + self.cols;
-        &self.data[start..end]
-    }
-}
-
-fn matches_pattern(grid: &CharGrid, (i, j): Point, (di, dj): Direction, pattern: &str) -> bool {
-    pattern.char_indices().all(|(k, ch)| {
-        let row = i.checked_add_signed(di * k as isize);
-        let col = j.checked_add_signed(dj * k as isize);
-
-        matches!((row, col), (Some(r), Some(c)) if r < grid.rows() && c < grid.cols() && grid[r][c] == ch)
-    })
+fn matches_pattern(grid: &Grid<char>, start: Point, d: Dir8, pattern: &str) -> bool {
+    let mut cells = grid.ray(start, d);
+    pattern
+        .chars()
+        .all(|ch| cells.next().is_some_and(|p| grid[p] == ch))
 }
 
 pub fn part_1(input: &str) -> Box<dyn Display> {
     let grid = parse(input);
-    #[rustfmt::skip]
-    let directions = [
-        (-1, -1), (-1,  0), (-1,  1),
-        ( 0, -1),           ( 0,  1),
-        ( 1, -1), ( 1,  0), ( 1,  1),
-    ];
 
     Box::new(
-        itertools::iproduct!(0..grid.rows(), 0..grid.cols(), directions)
-            .filter(|&(i, j, d)| grid[i][j] == 'X' && matches_pattern(&grid, (i, j), d, "XMAS"))
+        itertools::iproduct!(grid.positions(), Dir8::ALL)
+            .filter(|&(p, d)| grid[p] == 'X' && matches_pattern(&grid, p, d, "XMAS"))
             .count(),
     )
 }
 
 pub fn part_2(input: &str) -> Box<dyn Display> {
     let grid = parse(input);
-    let cross_directions = ((1, 1), (1, -1));
+    let cross_directions = (Dir8::SE, Dir8::SW);
     let patterns = ["MAS", "SAM"];
 
     Box::new(
         itertools::iproduct!(1..grid.rows() - 1, 1..grid.cols() - 1, patterns, patterns)
             .filter(|&(i, j, p1, p2)| {
-                grid[i][j] == 'A'
-                    && matches_pattern(&grid, (i - 1, j - 1), cross_directions.0, p1)
-                    && matches_pattern(&grid, (i - 1, j + 1), cross_directions.1, p2)
+                grid[Point(i, j)] == 'A'
+                    && matches_pattern(&grid, Point(i - 1, j - 1), cross_directions.0, p1)
+                    && matches_pattern(&grid, Point(i - 1, j + 1), cross_directions.1, p2)
             })
             .count(),
     )
 }
 
-fn parse(input: &str) -> CharGrid {
-    let mut cols = 0;
-    let data = input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| {
-            let data = l.trim().chars().collect::<Vec<_>>();
-            cols = data.len();
-            data
-        })
-        .flatten()
-        .collect();
+fn parse(input: &str) -> Grid<char> {
+    input.parse().unwrap()
+}
 
-    CharGrid { data, cols }
+/// Generates a `size` x `size` grid of random `XMAS` letters.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let letters = ['X', 'M', 'A', 'S'];
+    Grid::from_fn(size, size, |_| *letters.choose(rng).unwrap()).to_string()
 }
 
 #[cfg(test)]
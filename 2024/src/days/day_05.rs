@@ -1,87 +1,255 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    fmt::{self, Display, Write},
+    slice,
+};
 
-type OrderingRules = HashMap<u32, Vec<u32>>;
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::IndexedRandom, seq::SliceRandom, Rng};
+
+use aoc2024::utils::{
+    bitset::BitSet,
+    graph::{CycleError, DiGraph},
+    parse,
+};
+
+/// Edge `a -> b` for every `a|b` rule.
+type OrderingRules = DiGraph<u32>;
 type Updates = Vec<Vec<u32>>;
 
-fn is_ordered(ordering: &OrderingRules, update: &Vec<u32>) -> bool {
-    let idx_map: HashMap<_, _> = update.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+/// Rule `before|after` broken by an update, with the positions of both pages
+/// in it, counted from 1 like the updates in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Violation {
+    before: u32,
+    after: u32,
+    before_pos: usize,
+    after_pos: usize,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{}: {} at position {} comes after {} at position {}",
+            self.before, self.after, self.before, self.before_pos, self.after, self.after_pos
+        )
+    }
+}
 
-    idx_map.iter().all(|(page, p_idx)| {
-        ordering.get(page).is_none_or(|rules| {
-            rules
-                .iter()
-                .all(|p| idx_map.get(p).is_none_or(|r_idx| r_idx > p_idx))
+/// Every rule broken by the update, in order of the pages positions.
+fn violations<'a>(
+    ordering: &'a OrderingRules,
+    update: &'a [u32],
+) -> impl Iterator<Item = Violation> + 'a {
+    update
+        .iter()
+        .enumerate()
+        .tuple_combinations()
+        .filter(|((_, a), (_, b))| ordering.has_edge(b, a))
+        .map(|((i, &a), (j, &b))| Violation {
+            before: b,
+            after: a,
+            before_pos: j + 1,
+            after_pos: i + 1,
         })
-    })
 }
 
-fn ordered_mid_val(ordering: &OrderingRules, update: &mut Vec<u32>) -> u32 {
-    let len = update.len();
-
-    // Notice that to find the middle element in the correctly ordered update
-    // list we only need to fix the ordering up to the middle element.
-    for curr in 0..=len / 2 {
-        for next in curr..len {
-            match ordering.get(&update[next]) {
-                Some(r) => {
-                    if r.contains(&update[curr]) {
-                        update.swap(curr, next);
-                    }
-                }
-                _ => (),
-            }
+/// Dense bit matrix of the rules, bit `a * size + b` being set for `a|b`,
+/// to compare two pages in constant time. Pages are small numbers, so the
+/// matrix is indexed by page directly and sized by the largest one.
+struct Precedence<'a> {
+    ordering: &'a OrderingRules,
+    bits: BitSet,
+    size: usize,
+}
+
+impl<'a> Precedence<'a> {
+    fn new(ordering: &'a OrderingRules) -> Self {
+        let size = ordering.nodes().max().map_or(0, |&p| p as usize + 1);
+        let mut bits = BitSet::new(size * size);
+        for (&a, &b) in ordering.edges() {
+            bits.insert(a as usize * size + b as usize);
+        }
+        Self {
+            ordering,
+            bits,
+            size,
+        }
+    }
+
+    #[inline]
+    fn before(&self, a: u32, b: u32) -> bool {
+        let (a, b) = (a as usize, b as usize);
+        a < self.size && b < self.size && self.bits.contains(a * self.size + b)
+    }
+
+    #[inline]
+    fn cmp(&self, a: u32, b: u32) -> Ordering {
+        match (self.before(a, b), self.before(b, a)) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => Ordering::Equal,
+        }
+    }
+
+    /// Whether no rule is broken by any pair of pages of the update.
+    fn is_ordered(&self, update: &[u32]) -> bool {
+        update
+            .iter()
+            .tuple_combinations()
+            .all(|(&a, &b)| !self.before(b, a))
+    }
+
+    /// Whether [`Self::cmp`] is a total order on the pages of the update,
+    /// i.e. the number of pages each one must precede is a distinct value of
+    /// `0..n`. Missing rules or cycles make some of these counts equal.
+    fn is_total(&self, update: &[u32]) -> bool {
+        let mut ranks = BitSet::new(update.len());
+        update.iter().all(|&a| {
+            let rank = update.iter().filter(|&&b| self.before(a, b)).count();
+            rank < update.len() && ranks.insert(rank)
+        })
+    }
+
+    /// Sorts the update by comparing pages when the rules totally order it,
+    /// and by topological order of the rules otherwise, so that cycles are
+    /// reported.
+    fn sorted(&self, update: &[u32]) -> Result<Vec<u32>, CycleError<u32>> {
+        if !self.is_total(update) {
+            return reorder(self.ordering, update);
         }
+        let mut sorted = update.to_vec();
+        sorted.sort_by(|&a, &b| self.cmp(a, b));
+        Ok(sorted)
     }
-    update[len / 2]
 }
 
-pub fn part_1(input: &str) -> Box<dyn Display> {
-    let (ordering, updates) = parse(input);
-    Box::new(
+/// Sorts the whole update by topological order of the rules between its
+/// pages, rules about other pages being ignored.
+fn reorder(ordering: &OrderingRules, update: &[u32]) -> Result<Vec<u32>, CycleError<u32>> {
+    ordering.topo_sort_subset(update)
+}
+
+pub fn part_1(input: &str) -> Result<Box<dyn Display>, String> {
+    let (ordering, updates) = parse(input)?;
+    let precedence = Precedence::new(&ordering);
+    Ok(Box::new(
         updates
             .iter()
-            .filter_map(|u| is_ordered(&ordering, u).then(|| u[u.len() / 2]))
+            .filter(|u| precedence.is_ordered(u))
+            .map(|u| u[u.len() / 2])
             .sum::<u32>(),
-    )
+    ))
 }
 
-pub fn part_2(input: &str) -> Box<dyn Display> {
-    let (ordering, mut updates) = parse(input);
-    Box::new(
-        updates
-            .iter_mut()
-            .filter(|u| !is_ordered(&ordering, u))
-            .map(|u| ordered_mid_val(&ordering, u))
-            .sum::<u32>(),
-    )
+pub fn part_2(input: &str) -> Result<Box<dyn Display>, String> {
+    let (ordering, updates) = parse(input)?;
+    let precedence = Precedence::new(&ordering);
+    let mut sum = 0;
+    for (i, update) in updates.iter().enumerate() {
+        if precedence.is_ordered(update) {
+            continue;
+        }
+        let sorted = precedence
+            .sorted(update)
+            .map_err(|e| format!("update {}: {e}", i + 1))?;
+        sum += sorted[sorted.len() / 2];
+    }
+    Ok(Box::new(sum))
 }
 
-fn parse(input: &str) -> (OrderingRules, Updates) {
-    let mut blocks = input.split("\n\n");
-    let mut ordering = HashMap::<u32, Vec<u32>>::new();
+/// Lists the rules broken by each rejected update, to audit inputs.
+pub fn explain(input: &str) -> Result<String, String> {
+    let (ordering, updates) = parse(input)?;
+    let mut report = String::new();
 
-    blocks.next().unwrap().lines().for_each(|l| {
-        let [a, b] = l
-            .split('|')
-            .map(|d| d.parse().unwrap())
-            .collect::<Vec<u32>>()
-            .try_into()
-            .unwrap();
+    for (i, update) in updates.iter().enumerate() {
+        let broken = violations(&ordering, update).collect::<Vec<_>>();
+        if broken.is_empty() {
+            continue;
+        }
+        let _ = writeln!(report, "Update {} ({}):", i + 1, update.iter().join(","));
+        for v in broken {
+            let _ = writeln!(report, "  {v}");
+        }
+    }
+    Ok(report)
+}
 
-        ordering
-            .entry(a)
-            .and_modify(|v| v.push(b))
-            .or_insert(vec![b]);
-    });
+/// Graphviz DOT graph of the rules, restricted to the pages of an update
+/// (counted from 1) if given. Rules broken by that update, or by any update
+/// without one, are drawn in red.
+pub fn dot(input: &str, update: Option<usize>) -> Result<String, String> {
+    let (ordering, updates) = parse(input)?;
+    let (graph, selected) = match update {
+        Some(n) => {
+            let u = n
+                .checked_sub(1)
+                .and_then(|i| updates.get(i))
+                .ok_or_else(|| format!("no update {n}, there are {}", updates.len()))?;
+            (ordering.induced_subgraph(u), slice::from_ref(u))
+        }
+        None => (ordering.clone(), updates.as_slice()),
+    };
 
-    let updates = blocks
+    let broken = selected
+        .iter()
+        .flat_map(|u| violations(&ordering, u))
+        .map(|v| (v.before, v.after))
+        .collect::<HashSet<_>>();
+    Ok(graph.to_dot(|&a, &b| broken.contains(&(a, b)).then(|| "color=red".to_owned())))
+}
+
+fn parse(input: &str) -> Result<(OrderingRules, Updates), String> {
+    let mut blocks = parse::paragraphs(input);
+
+    let ordering = blocks
         .next()
-        .unwrap()
-        .lines()
-        .map(|l| l.split(',').map(|d| d.parse().unwrap()).collect())
+        .ok_or("missing ordering rules")?
+        .parse_lines(|l| parse::pair(l, "|"))
+        .map_err(|e| e.to_string())?
+        .into_iter()
         .collect();
 
-    (ordering, updates)
+    let updates = blocks
+        .next()
+        .ok_or("missing updates")?
+        .parse_lines(|l| parse::list(l, ","))
+        .map_err(|e| e.to_string())?;
+
+    Ok((ordering, updates))
+}
+
+/// Generates ordering rules for a random set of pages and `size` updates.
+/// The rules are derived from a single total order of the pages, so every
+/// update has exactly one correct ordering.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    let mut pages = (10..100).collect::<Vec<u32>>();
+    pages.shuffle(rng);
+    pages.truncate(49);
+
+    let mut rules = pages.iter().tuple_combinations::<(_, _)>().collect::<Vec<_>>();
+    rules.shuffle(rng);
+
+    let mut input = String::new();
+    for (a, b) in rules {
+        input.push_str(&format!("{a}|{b}\n"));
+    }
+    input.push('\n');
+
+    for _ in 0..size {
+        let len = 2 * rng.random_range(2..12) + 1;
+        let mut update = pages.choose_multiple(rng, len).collect::<Vec<_>>();
+        // Keep about half of the updates in the correct order.
+        match rng.random_bool(0.5) {
+            true => update.sort_by_key(|p| pages.iter().position(|o| o == *p)),
+            false => update.shuffle(rng),
+        }
+        input.push_str(&format!("{}\n", update.iter().join(",")));
+    }
+    input
 }
 
 #[cfg(test)]
@@ -122,13 +290,170 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let res = part_1(TEST_INPUT);
+        let res = part_1(TEST_INPUT).unwrap();
         assert_eq!(&res.to_string(), "143");
     }
 
     #[test]
     fn test_part_2() {
-        let res = part_2(TEST_INPUT);
+        let res = part_2(TEST_INPUT).unwrap();
         assert_eq!(&res.to_string(), "123");
     }
+
+    #[test]
+    fn test_violations() {
+        let (ordering, updates) = parse(TEST_INPUT).unwrap();
+        assert_eq!(
+            violations(&ordering, &updates[3]).collect::<Vec<_>>(),
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_pos: 2,
+                after_pos: 1,
+            }]
+        );
+        assert_eq!(violations(&ordering, &updates[5]).count(), 4);
+
+        let report = explain(TEST_INPUT).unwrap();
+        assert_eq!(report.lines().filter(|l| l.starts_with("Update")).count(), 3);
+        assert!(report.contains(
+            "Update 5 (61,13,29):\n  29|13: 29 at position 3 comes after 13 at position 2"
+        ));
+    }
+
+    #[test]
+    fn test_dot() {
+        let res = dot(TEST_INPUT, Some(4)).unwrap();
+        assert_eq!(res.lines().filter(|l| l.contains("->")).count(), 10);
+        assert!(res.contains("\"97\" -> \"75\" [color=red];"));
+        assert_eq!(res.matches("color=red").count(), 1);
+
+        let res = dot(TEST_INPUT, None).unwrap();
+        assert_eq!(res.lines().filter(|l| l.contains("->")).count(), 21);
+        assert!(dot(TEST_INPUT, Some(7)).is_err());
+    }
+
+    #[test]
+    fn test_reorder() {
+        let (ordering, _) = parse(TEST_INPUT).unwrap();
+        assert_eq!(
+            reorder(&ordering, &[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+
+        let cyclic: OrderingRules = [(1, 2), (2, 3), (3, 1)].into_iter().collect();
+        assert_eq!(reorder(&cyclic, &[3, 1]), Ok(vec![3, 1]));
+        assert!(reorder(&cyclic, &[2, 3, 1]).is_err());
+    }
+
+    #[test]
+    fn test_generate() {
+        use rand::SeedableRng;
+
+        let input = generate(&mut StdRng::seed_from_u64(0), 20);
+        let (ordering, updates) = parse(&input).unwrap();
+        let precedence = Precedence::new(&ordering);
+        assert_eq!(updates.len(), 20);
+        assert!(updates.iter().all(|u| u.len() % 2 == 1));
+        assert!(updates.iter().any(|u| precedence.is_ordered(u)));
+        assert!(updates.iter().any(|u| !precedence.is_ordered(u)));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let (ordering, updates) = parse(TEST_INPUT).unwrap();
+        let precedence = Precedence::new(&ordering);
+        assert!(precedence.before(47, 53) && !precedence.before(53, 47));
+        assert_eq!(precedence.cmp(13, 200), Ordering::Equal);
+
+        for u in &updates {
+            let ordered = violations(&ordering, u).next().is_none();
+            assert_eq!(precedence.is_ordered(u), ordered);
+            assert!(precedence.is_total(u));
+        }
+    }
+
+    #[test]
+    fn test_sparse_rules() {
+        let ordering: OrderingRules = [(1, 3), (4, 5)].into_iter().collect();
+        let precedence = Precedence::new(&ordering);
+        assert!(!precedence.is_ordered(&[3, 2, 1]));
+        assert!(!precedence.is_total(&[3, 2, 1]));
+        assert_eq!(precedence.sorted(&[3, 2, 1]), Ok(vec![2, 1, 3]));
+        assert_eq!(precedence.sorted(&[5, 4]), Ok(vec![4, 5]));
+
+        let res = part_2("1|3\n4|5\n\n3,2,1\n5,4,6\n1,2,3\n").unwrap();
+        assert_eq!(&res.to_string(), "7");
+    }
+
+    #[test]
+    fn test_cyclic_rules() {
+        let ordering: OrderingRules = [(1, 2), (2, 3), (3, 1)].into_iter().collect();
+        let precedence = Precedence::new(&ordering);
+        assert!(!precedence.is_total(&[1, 2, 3]));
+        assert!(precedence.sorted(&[1, 2, 3]).is_err());
+        assert!(precedence.is_total(&[3, 1]));
+        assert_eq!(precedence.sorted(&[1, 3]), Ok(vec![3, 1]));
+
+        // Every pair is ordered, but around a cycle, which `sort_by` can't
+        // handle.
+        let pages = (1..=41).collect::<Vec<u32>>();
+        let ordering: OrderingRules = pages
+            .iter()
+            .tuple_combinations()
+            .map(|(&a, &b)| match (b - a) % 2 {
+                1 => (a, b),
+                _ => (b, a),
+            })
+            .collect();
+        let precedence = Precedence::new(&ordering);
+        assert!(!precedence.is_total(&pages));
+        assert!(precedence.sorted(&pages).is_err());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(
+            part_2("1|2\n2|3\n3|1\n\n1,2,3\n").err(),
+            Some("update 1: graph has a cycle: 1 -> 2 -> 3 -> 1".to_owned())
+        );
+        assert_eq!(
+            part_1("1|2\n2|x\n\n1,2\n").err(),
+            Some("line 2, column 3: invalid value \"x\"".to_owned())
+        );
+        assert_eq!(part_1("").err(), Some("missing ordering rules".to_owned()));
+        assert_eq!(part_1("1|2\n").err(), Some("missing updates".to_owned()));
+    }
+
+    /// Compares the rule graph with the bit matrix on a large generated
+    /// input. Run with `cargo test --release bench -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn bench_ordering() {
+        use rand::SeedableRng;
+        use std::time::Instant;
+
+        let input = generate(&mut StdRng::seed_from_u64(0), 20_000);
+        let (ordering, updates) = parse(&input).unwrap();
+
+        let time = Instant::now();
+        let graph_sum = updates
+            .iter()
+            .filter(|u| violations(&ordering, u).next().is_some())
+            .map(|u| reorder(&ordering, u).unwrap()[u.len() / 2])
+            .sum::<u32>();
+        let graph_time = time.elapsed();
+
+        let time = Instant::now();
+        let precedence = Precedence::new(&ordering);
+        let matrix_sum = updates
+            .iter()
+            .filter(|u| !precedence.is_ordered(u))
+            .map(|u| precedence.sorted(u).unwrap()[u.len() / 2])
+            .sum::<u32>();
+        let matrix_time = time.elapsed();
+
+        assert_eq!(graph_sum, matrix_sum);
+        println!("rule graph: {graph_time:?}, bit matrix: {matrix_time:?}");
+    }
 }
@@ -1,96 +1,680 @@
-use std::{collections::HashSet, fmt::Display};
+use std::{
+    collections::HashSet,
+    fmt::{Display, Write},
+    io::{self, BufRead, Write as _},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
 
-type Map = Vec<Vec<char>>; // 2D char grid
-type Pos = (usize, usize); // (i, j)
+use colored::Colorize;
+use rand::{rngs::StdRng, Rng};
 
-struct Direction(isize, isize); // (delta_i, delta_j)
+use aoc2024::utils::{
+    bitset::{GridDirSet, GridSet},
+    geometry::{Dir4, Point},
+    grid::{Grid, ParseGridError},
+    term::Screen,
+};
 
-impl Direction {
+type Map = Grid<char>;
+
+#[derive(Clone)]
+struct Guard {
+    pos: Point,
+    direction: Dir4,
+}
+
+/// What a guard did on a single [`Guard::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepOutcome {
+    /// Walked one cell forward, to the given position.
+    Moved(Point),
+    /// Faced an obstacle and turned right, possibly several times, until
+    /// facing the given free direction.
+    Turned(Dir4),
+    /// Walked off the map.
+    Exited,
+    /// Boxed in by obstacles on all four sides, spinning forever.
+    Looped,
+}
+
+impl Guard {
     #[inline]
-    pub fn rotate_right(&mut self) {
-        let temp = self.0;
-        self.0 = self.1;
-        self.1 = -1 * temp;
+    pub fn curr_pos(&self) -> Point {
+        self.pos
+    }
+
+    /// Next cell ahead, `None` if it's off the map.
+    fn ahead(&self, map: &Map) -> Option<Point> {
+        self.pos.checked_step(self.direction, map.bounds())
     }
-}
 
-impl Default for Direction {
-    fn default() -> Self {
-        Self(-1, 0)
+    fn is_blocked(&self, map: &Map) -> bool {
+        self.ahead(map).is_some_and(|next| map[next] == '#')
+    }
+
+    pub fn step(&mut self, map: &Map) -> StepOutcome {
+        if self.is_blocked(map) {
+            for _ in 0..3 {
+                self.direction = self.direction.rotate_right();
+                if !self.is_blocked(map) {
+                    return StepOutcome::Turned(self.direction);
+                }
+            }
+            self.direction = self.direction.rotate_right();
+            return StepOutcome::Looped;
+        }
+
+        match self.ahead(map) {
+            Some(next) => {
+                self.pos = next;
+                StepOutcome::Moved(next)
+            }
+            None => StepOutcome::Exited,
+        }
     }
 }
 
-struct Guard {
-    pos: Pos,
-    direction: Direction,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MissingGuardError;
+
+impl Display for MissingGuardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no guard on the map, expected one of ^ > v <")
+    }
 }
 
-struct GuardPosError;
+/// Patrol of a guard that walks in circles, or is boxed in, so it has no
+/// end to count cells on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EndlessPatrolError;
+
+impl Display for EndlessPatrolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "guard never leaves the map")
+    }
+}
 
 impl Guard {
-    #[inline]
-    pub fn curr_pos(&self) -> Pos {
-        self.pos
+    /// Every guard on the map in row-major order, each heading where its
+    /// marker points.
+    fn find_all(map: &Map) -> Result<Vec<Self>, MissingGuardError> {
+        let guards = map
+            .positions()
+            .filter_map(|pos| {
+                marker_heading(map[pos]).map(|direction| Self {
+                    pos,
+                    direction,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        match guards.is_empty() {
+            true => Err(MissingGuardError),
+            false => Ok(guards),
+        }
+    }
+}
+
+/// Cells the guard goes through before leaving the map, or an error if it
+/// reaches a position and heading it already had.
+fn patrol(map: &Map, mut guard: Guard) -> Result<GridSet, EndlessPatrolError> {
+    let mut states = GridDirSet::new(map.bounds());
+    states.insert(guard.curr_pos(), guard.direction);
+
+    loop {
+        match guard.step(map) {
+            StepOutcome::Moved(_) | StepOutcome::Turned(_) => {
+                if !states.insert(guard.curr_pos(), guard.direction) {
+                    return Err(EndlessPatrolError);
+                }
+            }
+            StepOutcome::Exited => return Ok(states.cells()),
+            StepOutcome::Looped => return Err(EndlessPatrolError),
+        }
+    }
+}
+
+/// Whether the guard ends up walking in circles, i.e. reaches a position
+/// and heading it already had.
+fn is_looping(map: &Map, mut guard: Guard) -> bool {
+    let mut states = GridDirSet::new(map.bounds());
+    states.insert(guard.curr_pos(), guard.direction);
+
+    loop {
+        match guard.step(map) {
+            StepOutcome::Moved(_) | StepOutcome::Turned(_) => {
+                if !states.insert(guard.curr_pos(), guard.direction) {
+                    return true;
+                }
+            }
+            StepOutcome::Exited => return false,
+            StepOutcome::Looped => return true,
+        }
     }
+}
+
+/// Obstacles of every row and column, sorted, to find the next one ahead of
+/// a guard without walking there.
+struct JumpTable {
+    /// Columns of the obstacles of each row.
+    rows: Vec<Vec<usize>>,
+    /// Rows of the obstacles of each column.
+    cols: Vec<Vec<usize>>,
+}
 
-    pub fn step(&mut self, map: &Map) -> Result<Pos, GuardPosError> {
-        let (rows, cols) = (map.len(), map[0].len());
-        let next_pos = |(i, j): Pos, dir: &Direction| {
-            Ok((
-                i.checked_add_signed(dir.0)
-                    .and_then(|r| (r < rows).then_some(r))
-                    .ok_or(GuardPosError)?,
-                j.checked_add_signed(dir.1)
-                    .and_then(|c| (c < cols).then_some(c))
-                    .ok_or(GuardPosError)?,
-            ))
+impl JumpTable {
+    fn new(map: &Map) -> Self {
+        let mut table = Self {
+            rows: vec![vec![]; map.rows()],
+            cols: vec![vec![]; map.cols()],
         };
+        // Positions come in row-major order, so both lists stay sorted.
+        for Point(i, j) in map.find_all(&'#') {
+            table.rows[i].push(j);
+            table.cols[j].push(i);
+        }
+        table
+    }
 
-        let next = next_pos(self.pos, &self.direction)?;
+    fn insert(&mut self, Point(i, j): Point) {
+        if let Err(k) = self.rows[i].binary_search(&j) {
+            self.rows[i].insert(k, j);
+        }
+        if let Err(k) = self.cols[j].binary_search(&i) {
+            self.cols[j].insert(k, i);
+        }
+    }
 
-        if map[next.0][next.1] == '#' {
-            self.direction.rotate_right();
+    fn remove(&mut self, Point(i, j): Point) {
+        if let Ok(k) = self.rows[i].binary_search(&j) {
+            self.rows[i].remove(k);
+        }
+        if let Ok(k) = self.cols[j].binary_search(&i) {
+            self.cols[j].remove(k);
         }
-        self.pos = next_pos(self.pos, &self.direction)?;
-        Ok(self.pos)
     }
+
+    /// Cell where a guard walking straight from `pos` stops, in front of the
+    /// next obstacle, `None` if it walks off the map instead.
+    fn next_stop(&self, Point(i, j): Point, dir: Dir4) -> Option<Point> {
+        let after = |line: &[usize], k: usize| line.get(line.partition_point(|&o| o <= k)).copied();
+        let before = |line: &[usize], k: usize| {
+            let idx = line.partition_point(|&o| o < k);
+            idx.checked_sub(1).map(|idx| line[idx])
+        };
+
+        match dir {
+            Dir4::Up => before(&self.cols[j], i).map(|o| Point(o + 1, j)),
+            Dir4::Right => after(&self.rows[i], j).map(|o| Point(i, o - 1)),
+            Dir4::Down => after(&self.cols[j], i).map(|o| Point(o - 1, j)),
+            Dir4::Left => before(&self.rows[i], j).map(|o| Point(i, o + 1)),
+        }
+    }
+
+    /// Same as [`is_looping`], jumping from turn to turn. Only the states
+    /// after turns are tracked, since any loop goes through one.
+    fn is_looping(&self, (rows, cols): (usize, usize), mut guard: Guard) -> bool {
+        let mut states = GridDirSet::new((rows, cols));
+
+        while let Some(stop) = self.next_stop(guard.pos, guard.direction) {
+            guard.pos = stop;
+
+            // An obstacle right in front stops the guard where it stands.
+            let mut turns = 0;
+            while self.next_stop(guard.pos, guard.direction) == Some(guard.pos) {
+                guard.direction = guard.direction.rotate_right();
+                turns += 1;
+                if turns == 4 {
+                    return true;
+                }
+            }
+            if !states.insert(guard.pos, guard.direction) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Cells any of the guards goes through. Guards don't block each other.
+fn patrol_all(map: &Map, guards: &[Guard]) -> Result<GridSet, EndlessPatrolError> {
+    let mut visited_pos = GridSet::new(map.bounds());
+    for guard in guards {
+        for pos in patrol(map, guard.clone())?.iter() {
+            visited_pos.insert(pos);
+        }
+    }
+    Ok(visited_pos)
+}
+
+pub fn part_1(input: &str) -> Result<Box<dyn Display>, String> {
+    let (map, guards) = parse(input)?;
+    let visited = patrol_all(&map, &guards).map_err(|e| e.to_string())?;
+    Ok(Box::new(visited.count()))
+}
+
+pub fn part_2(input: &str) -> Result<Box<dyn Display>, String> {
+    let (map, guards) = parse(input)?;
+
+    // An obstacle off the original paths is never met, so it can't change
+    // them, and it can't be put on a guard.
+    let candidates = patrol_all(&map, &guards)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|&pos| guards.iter().all(|g| g.curr_pos() != pos))
+        .collect::<Vec<_>>();
+
+    let mut table = JumpTable::new(&map);
+    let mut count = 0;
+    for pos in candidates {
+        table.insert(pos);
+        if guards
+            .iter()
+            .any(|g| table.is_looping(map.bounds(), g.clone()))
+        {
+            count += 1;
+        }
+        table.remove(pos);
+    }
+    Ok(Box::new(count))
 }
 
-impl From<&Map> for Guard {
-    fn from(map: &Map) -> Self {
-        let mut pos = (0, 0);
-        for (i, row) in map.iter().enumerate() {
-            if let Some(j) = row.iter().position(|&c| c == '^') {
-                pos = (i, j);
-                break;
+/// Arrow showing where the guard is heading.
+fn heading_marker(dir: Dir4) -> char {
+    match dir {
+        Dir4::Up => '^',
+        Dir4::Right => '>',
+        Dir4::Down => 'v',
+        Dir4::Left => '<',
+    }
+}
+
+/// Heading of a guard start marker, `None` if `c` isn't one.
+fn marker_heading(c: char) -> Option<Dir4> {
+    Dir4::ALL.into_iter().find(|&d| heading_marker(d) == c)
+}
+
+fn render(map: &Map, guards: &[Guard], visited: &GridSet) -> String {
+    let mut frame = String::new();
+    for (i, row) in map.iter_rows().enumerate() {
+        for (j, &c) in row.iter().enumerate() {
+            let guard = guards.iter().find(|g| g.curr_pos() == Point(i, j));
+            let cell = match (c, guard) {
+                ('#', _) => "#".red().bold(),
+                (_, Some(guard)) => heading_marker(guard.direction).to_string().yellow().bold(),
+                _ if visited.contains(Point(i, j)) => "X".blue(),
+                _ => ".".dimmed(),
+            };
+            let _ = write!(frame, "{cell}");
+        }
+        frame.push('\n');
+    }
+    frame
+}
+
+/// Animates the patrol in the terminal, every guard taking one step per
+/// frame, until they all left the map or started looping.
+pub fn visualize(input: &str, fps: u32) {
+    let (map, mut guards) = match parse(input) {
+        Ok(parsed) => parsed,
+        Err(e) => return eprintln!("{}", e.to_string().red()),
+    };
+
+    let mut visited = GridSet::new(map.bounds());
+    let mut states = vec![GridDirSet::new(map.bounds()); guards.len()];
+    for (guard, states) in guards.iter().zip(&mut states) {
+        visited.insert(guard.curr_pos());
+        states.insert(guard.curr_pos(), guard.direction);
+    }
+    let mut done = vec![false; guards.len()];
+
+    let mut screen = Screen::new();
+    let delay = Duration::from_secs(1) / fps.max(1);
+    let draw = |screen: &mut Screen, guards: &[Guard], visited: &GridSet, status: &str| {
+        let frame = render(&map, guards, visited);
+        let footer = format!("{status}\n{} cells visited", visited.count());
+        screen.draw(&format!("{frame}\n{footer}\n"))
+    };
+
+    if draw(&mut screen, &guards, &visited, "Step 0: Start").is_err() {
+        return;
+    }
+
+    for step in 1.. {
+        thread::sleep(delay);
+
+        let mut outcomes = vec![];
+        for (k, guard) in guards.iter_mut().enumerate() {
+            // Finished guards stay where they are.
+            if done[k] {
+                outcomes.push("Done".to_owned());
+                continue;
+            }
+
+            let outcome = guard.step(&map);
+            if let StepOutcome::Moved(pos) = outcome {
+                visited.insert(pos);
             }
+            done[k] = match outcome {
+                StepOutcome::Moved(_) | StepOutcome::Turned(_) => {
+                    !states[k].insert(guard.curr_pos(), guard.direction)
+                }
+                StepOutcome::Exited | StepOutcome::Looped => true,
+            };
+            outcomes.push(match done[k] && outcome != StepOutcome::Exited {
+                true => "Looped".to_owned(),
+                false => format!("{outcome:?}"),
+            });
         }
+
+        let status = format!("Step {step}: {}", outcomes.join(" | "));
+        if draw(&mut screen, &guards, &visited, &status).is_err() || done.iter().all(|&d| d) {
+            return;
+        }
+    }
+}
+
+/// Command of the interactive [`debug`] mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DebugCommand {
+    Next(usize),
+    Back(usize),
+    Goto(usize),
+    Continue,
+    BreakCell(Point),
+    BreakTurn,
+    ClearBreaks,
+    Obstacle(Point),
+    Help,
+    Quit,
+}
+
+const DEBUG_HELP: &str = "\
+n [k]      step forward k steps (default 1, also on empty line)
+b [k]      step backward k steps (default 1)
+g <n>      jump to step n
+c          continue until a breakpoint, the exit or a loop
+bc <i> <j> toggle a breakpoint on cell (i, j)
+bt         toggle a breakpoint on every turn
+bx         clear breakpoints
+o <i> <j>  insert or remove an obstacle on cell (i, j)
+h          show this help
+q          quit";
+
+impl FromStr for DebugCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let cmd = words.next().unwrap_or("n");
+        let args = words
+            .map(|w| w.parse().map_err(|_| format!("invalid number `{w}`")))
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        Ok(match (cmd, args.as_slice()) {
+            ("n", []) => Self::Next(1),
+            ("n", &[k]) => Self::Next(k),
+            ("b", []) => Self::Back(1),
+            ("b", &[k]) => Self::Back(k),
+            ("g", &[n]) => Self::Goto(n),
+            ("c", []) => Self::Continue,
+            ("bc", &[i, j]) => Self::BreakCell(Point(i, j)),
+            ("bt", []) => Self::BreakTurn,
+            ("bx", []) => Self::ClearBreaks,
+            ("o", &[i, j]) => Self::Obstacle(Point(i, j)),
+            ("h", []) => Self::Help,
+            ("q", []) => Self::Quit,
+            _ => return Err(format!("unknown command `{}`, try `h`", s.trim())),
+        })
+    }
+}
+
+/// Replays a patrol step by step, keeping every state so it can go back.
+/// Every guard takes one step at a time.
+struct Debugger {
+    map: Map,
+    /// Guards after each step, the first ones being the starts.
+    history: Vec<Vec<Guard>>,
+    /// Outcomes of the step leading to each state after the first one.
+    outcomes: Vec<Vec<StepOutcome>>,
+    /// Set once every guard exited or got boxed in, no state follows.
+    finished: bool,
+    cursor: usize,
+    cell_breaks: HashSet<Point>,
+    turn_break: bool,
+}
+
+impl Debugger {
+    fn new(map: Map, guards: Vec<Guard>) -> Self {
         Self {
-            pos,
-            direction: Direction::default(),
+            map,
+            history: vec![guards],
+            outcomes: vec![],
+            finished: false,
+            cursor: 0,
+            cell_breaks: HashSet::new(),
+            turn_break: false,
+        }
+    }
+
+    #[inline]
+    fn guards(&self) -> &[Guard] {
+        &self.history[self.cursor]
+    }
+
+    /// Steps after which every guard still on the map has repeated a (cell,
+    /// heading) state, simulating further would only replay loops.
+    fn max_steps(&self) -> usize {
+        let (rows, cols) = self.map.bounds();
+        4 * rows * cols
+    }
+
+    /// Simulates up to step `n`, or [`Self::max_steps`] if lower, returning
+    /// the last step reached.
+    fn simulate_to(&mut self, n: usize) -> usize {
+        let n = n.min(self.max_steps());
+        while self.history.len() <= n && !self.finished {
+            let mut guards = self.history.last().unwrap().clone();
+            let outcomes = guards
+                .iter_mut()
+                .map(|g| g.step(&self.map))
+                .collect::<Vec<_>>();
+
+            // Keep the final outcomes even though the guards are done.
+            self.finished = outcomes
+                .iter()
+                .all(|o| matches!(o, StepOutcome::Exited | StepOutcome::Looped));
+            if !self.finished {
+                self.history.push(guards);
+            }
+            self.outcomes.push(outcomes);
+        }
+        n.min(self.history.len() - 1)
+    }
+
+    fn goto(&mut self, n: usize) {
+        self.cursor = self.simulate_to(n);
+    }
+
+    fn is_breakpoint(&self, step: usize) -> bool {
+        let on_cell = self.history[step]
+            .iter()
+            .any(|g| self.cell_breaks.contains(&g.pos));
+        let turned = self.outcomes[step - 1]
+            .iter()
+            .any(|o| matches!(o, StepOutcome::Turned(_)));
+        on_cell || self.turn_break && turned
+    }
+
+    /// Steps forward until a breakpoint, the end of the patrol, or
+    /// [`Self::max_steps`] for a looping one.
+    fn resume(&mut self) {
+        loop {
+            let next = self.cursor + 1;
+            if self.simulate_to(next) != next {
+                return;
+            }
+            self.cursor = next;
+            if self.is_breakpoint(next) {
+                return;
+            }
+        }
+    }
+
+    /// Adds an obstacle on `pos`, or removes it, and replays the patrol up
+    /// to the current step.
+    fn toggle_obstacle(&mut self, pos: Point) -> Result<(), String> {
+        if !self.map.in_bounds(pos) {
+            return Err(format!("{pos:?} is out of the map"));
+        }
+        if self.history[0].iter().any(|g| g.pos == pos) {
+            return Err("can't put an obstacle on a guard's start".to_owned());
+        }
+
+        self.map[pos] = match self.map[pos] {
+            '#' => '.',
+            _ => '#',
+        };
+        self.history.truncate(1);
+        self.outcomes.clear();
+        self.finished = false;
+        self.goto(self.cursor);
+        Ok(())
+    }
+
+    /// Runs a command, returning a message to show, if any.
+    fn run(&mut self, cmd: DebugCommand) -> Result<Option<String>, String> {
+        match cmd {
+            DebugCommand::Next(k) => self.goto(self.cursor.saturating_add(k)),
+            DebugCommand::Back(k) => self.cursor = self.cursor.saturating_sub(k),
+            DebugCommand::Goto(n) => self.goto(n),
+            DebugCommand::Continue => self.resume(),
+            DebugCommand::BreakCell(pos) => {
+                if !self.cell_breaks.remove(&pos) {
+                    self.cell_breaks.insert(pos);
+                }
+            }
+            DebugCommand::BreakTurn => self.turn_break = !self.turn_break,
+            DebugCommand::ClearBreaks => {
+                self.cell_breaks.clear();
+                self.turn_break = false;
+            }
+            DebugCommand::Obstacle(pos) => self.toggle_obstacle(pos)?,
+            DebugCommand::Help => return Ok(Some(DEBUG_HELP.to_owned())),
+            DebugCommand::Quit => {}
+        }
+        Ok(None)
+    }
+
+    fn status(&self) -> String {
+        let last = self.finished && self.cursor == self.history.len() - 1;
+        let mut status = format!("Step {}:", self.cursor);
+
+        for (k, guard) in self.guards().iter().enumerate() {
+            let _ = write!(
+                status,
+                "\n  Guard {k} at {:?} heading {:?}",
+                guard.pos, guard.direction
+            );
+            if let Some(outcomes) = self.cursor.checked_sub(1).map(|i| &self.outcomes[i]) {
+                let _ = write!(status, " after {:?}", outcomes[k]);
+            }
+            if last {
+                let _ = write!(status, ", then {:?}", self.outcomes.last().unwrap()[k]);
+            }
+        }
+
+        let mut breaks = self.cell_breaks.iter().collect::<Vec<_>>();
+        breaks.sort();
+        let _ = write!(
+            status,
+            "\nBreakpoints: cells {breaks:?}, turns {}",
+            self.turn_break
+        );
+        status
+    }
+
+    fn render(&self) -> String {
+        let mut visited = GridSet::new(self.map.bounds());
+        for guard in self.history[..=self.cursor].iter().flatten() {
+            visited.insert(guard.pos);
         }
+        format!(
+            "{}\n{}",
+            render(&self.map, self.guards(), &visited),
+            self.status()
+        )
     }
 }
 
-pub fn part_1(input: &str) -> Box<dyn Display> {
-    let (map, mut guard) = parse(input);
-    let mut visited_pos = HashSet::<Pos>::new();
-    visited_pos.insert(guard.curr_pos());
+/// Interactive debugger reading commands from stdin, see [`DEBUG_HELP`].
+pub fn debug(input: &str) {
+    let (map, guards) = match parse(input) {
+        Ok(parsed) => parsed,
+        Err(e) => return eprintln!("{}", e.to_string().red()),
+    };
+    let mut debugger = Debugger::new(map, guards);
+
+    println!("{}\n\n{DEBUG_HELP}", debugger.render());
+    print!("> ");
+    let _ = io::stdout().flush();
 
-    while let Ok(pos) = guard.step(&map) {
-        visited_pos.insert(pos);
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        match line.parse::<DebugCommand>() {
+            Ok(DebugCommand::Quit) => break,
+            Ok(cmd) => match debugger.run(cmd) {
+                Ok(Some(msg)) => println!("{msg}"),
+                Ok(None) => println!("{}", debugger.render()),
+                Err(e) => eprintln!("{}", e.red()),
+            },
+            Err(e) => eprintln!("{}", e.red()),
+        }
+        print!("> ");
+        let _ = io::stdout().flush();
     }
-    Box::new(visited_pos.len())
 }
 
-pub fn part_2(_input: &str) -> Box<dyn Display> {
-    Box::new(0)
+fn parse(input: &str) -> Result<(Map, Vec<Guard>), String> {
+    let map = input.parse().map_err(|e: ParseGridError| e.to_string())?;
+    let guards = Guard::find_all(&map).map_err(|e| e.to_string())?;
+    Ok((map, guards))
 }
 
-fn parse(input: &str) -> (Map, Guard) {
-    let map = input.lines().map(|s| s.chars().collect()).collect();
-    let guard = Guard::from(&map);
-    (map, guard)
+/// Generates a `size` x `size` map with scattered obstacles and a guard the
+/// patrol of which eventually leaves the map.
+pub fn generate(rng: &mut StdRng, size: usize) -> String {
+    if size == 0 {
+        return String::new();
+    }
+
+    loop {
+        let mut map = Map::from_fn(size, size, |_| match rng.random_bool(0.05) {
+            true => '#',
+            false => '.',
+        });
+
+        let Some(start) = (0..size * size)
+            .map(|_| Point(rng.random_range(0..size), rng.random_range(0..size)))
+            .find(|&p| map[p] == '.')
+        else {
+            continue;
+        };
+        let direction = Dir4::ALL[rng.random_range(0..4)];
+        map[start] = heading_marker(direction);
+
+        let guard = Guard {
+            pos: start,
+            direction,
+        };
+        if !is_looping(&map, guard) {
+            return map.to_string();
+        }
+    }
 }
 
 #[cfg(test)]
@@ -113,13 +697,163 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let res = part_1(TEST_INPUT);
+        let res = part_1(TEST_INPUT).unwrap();
         assert_eq!(&res.to_string(), "41");
     }
 
     #[test]
     fn test_part_2() {
-        let res = part_2(TEST_INPUT);
+        let res = part_2(TEST_INPUT).unwrap();
         assert_eq!(&res.to_string(), "6");
     }
+
+    #[test]
+    fn test_generate() {
+        use rand::SeedableRng;
+
+        let input = generate(&mut StdRng::seed_from_u64(0), 50);
+        let (map, guards) = parse(&input).unwrap();
+        assert_eq!((map.rows(), map.cols()), (50, 50));
+        assert_eq!(guards.len(), 1);
+        assert!(!is_looping(&map, guards[0].clone()));
+    }
+
+    #[test]
+    fn test_jump_table() {
+        use rand::SeedableRng;
+
+        // Every extra obstacle has to give the same verdict both ways.
+        let input = generate(&mut StdRng::seed_from_u64(1), 30);
+        let (mut map, guards) = parse(&input).unwrap();
+        let mut table = JumpTable::new(&map);
+
+        for pos in map.positions().collect::<Vec<_>>() {
+            if map[pos] != '.' {
+                continue;
+            }
+            map[pos] = '#';
+            table.insert(pos);
+            assert_eq!(
+                table.is_looping(map.bounds(), guards[0].clone()),
+                is_looping(&map, guards[0].clone()),
+                "obstacle on {pos:?}"
+            );
+            map[pos] = '.';
+            table.remove(pos);
+        }
+    }
+
+    #[test]
+    fn test_debugger() {
+        let (map, guards) = parse(TEST_INPUT).unwrap();
+        let mut debugger = Debugger::new(map, guards);
+
+        debugger.run("n 5".parse().unwrap()).unwrap();
+        assert_eq!(debugger.guards()[0].pos, Point(1, 4));
+        debugger.run("b 2".parse().unwrap()).unwrap();
+        assert_eq!(debugger.guards()[0].pos, Point(3, 4));
+
+        // The first turn happens in front of the obstacle on (0, 4).
+        debugger.run("bt".parse().unwrap()).unwrap();
+        debugger.run("c".parse().unwrap()).unwrap();
+        assert_eq!(debugger.cursor, 6);
+        assert_eq!(debugger.guards()[0].direction, Dir4::Right);
+
+        debugger.run("bx".parse().unwrap()).unwrap();
+        debugger.run("c".parse().unwrap()).unwrap();
+        assert!(debugger.finished);
+
+        // Obstacle from the part 2 example, the guard now loops forever.
+        debugger.run("o 6 3".parse().unwrap()).unwrap();
+        debugger.run("g 0".parse().unwrap()).unwrap();
+        debugger.run("c".parse().unwrap()).unwrap();
+        assert!(!debugger.finished);
+        assert_eq!(debugger.cursor, debugger.max_steps());
+
+        // Jumps past the loop stop there instead of storing every step.
+        debugger.run("g 1000000000".parse().unwrap()).unwrap();
+        debugger.run(format!("n {}", usize::MAX).parse().unwrap()).unwrap();
+        assert_eq!(debugger.cursor, 400);
+        assert_eq!(debugger.history.len(), 401);
+        assert!(debugger.run("o 6 4".parse().unwrap()).is_err());
+        assert!("x 1".parse::<DebugCommand>().is_err());
+    }
+
+    #[test]
+    fn test_multiple_guards() {
+        let input = indoc! { r#"
+            ..#..
+            >....
+            .....
+            ..v..
+            .....
+        "# };
+        let (_, guards) = parse(input).unwrap();
+        let headings = guards.iter().map(|g| g.direction).collect::<Vec<_>>();
+        assert_eq!(headings, vec![Dir4::Right, Dir4::Down]);
+        assert_eq!(&part_1(input).unwrap().to_string(), "7");
+
+        assert_eq!(
+            parse("...\n.#.\n").err(),
+            Some(MissingGuardError.to_string())
+        );
+        assert!(parse("...\n.#\n").is_err());
+        assert!(part_1("").is_err());
+    }
+
+    fn single_guard(input: &str) -> (Map, Guard) {
+        let (map, mut guards) = parse(input).unwrap();
+        (map, guards.remove(0))
+    }
+
+    #[test]
+    fn test_step_corner() {
+        let (map, mut guard) = single_guard(indoc! { r#"
+            .#.
+            .^#
+            ...
+        "# });
+
+        assert_eq!(guard.step(&map), StepOutcome::Turned(Dir4::Down));
+        assert_eq!(guard.step(&map), StepOutcome::Moved(Point(2, 1)));
+        assert_eq!(guard.step(&map), StepOutcome::Exited);
+    }
+
+    #[test]
+    fn test_step_dead_end() {
+        let (map, mut guard) = single_guard(indoc! { r#"
+            .#.
+            #^#
+            ...
+        "# });
+        assert_eq!(guard.step(&map), StepOutcome::Turned(Dir4::Down));
+        assert_eq!(guard.step(&map), StepOutcome::Moved(Point(2, 1)));
+
+        let (map, mut guard) = single_guard(indoc! { r#"
+            .#.
+            #^#
+            .#.
+        "# });
+        assert_eq!(guard.step(&map), StepOutcome::Looped);
+        assert!(is_looping(&map, guard));
+        assert_eq!(
+            part_1(".#.\n#^#\n.#.\n").err(),
+            Some(EndlessPatrolError.to_string())
+        );
+    }
+
+    #[test]
+    fn test_endless_patrol() {
+        let input = indoc! { r#"
+            .#...
+            ....#
+            .^...
+            #....
+            ...#.
+        "# };
+        let (map, guard) = single_guard(input);
+        assert_eq!(patrol(&map, guard), Err(EndlessPatrolError));
+        assert_eq!(part_1(input).err(), Some(EndlessPatrolError.to_string()));
+        assert!(part_2(input).is_err());
+    }
 }
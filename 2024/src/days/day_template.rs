@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use rand::rngs::StdRng;
+
 pub fn part_1(_input: &str) -> Box<dyn Display> {
     Box::new(0)
 }
@@ -8,6 +10,10 @@ pub fn part_2(_input: &str) -> Box<dyn Display> {
     Box::new(0)
 }
 
+pub fn generate(_rng: &mut StdRng, _size: usize) -> String {
+    String::new()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
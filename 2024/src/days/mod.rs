@@ -1,47 +1,148 @@
+use std::cell::OnceCell;
 use std::collections::BTreeMap;
 use std::fmt::Display;
+use std::marker::PhantomData;
 use std::time::Instant;
 
 use colored::Colorize;
 use lazy_static::lazy_static;
 use paste::paste;
+use rand::rngs::StdRng;
 
-type SolutionFn = fn(&str) -> Box<dyn Display>;
+use crate::input::Input;
+
+type Answer = Result<Box<dyn Display>, String>;
+type SolutionFn<T> = Box<dyn Fn(&T) -> Answer + Send + Sync>;
+type GeneratorFn = fn(&mut StdRng, usize) -> String;
+
+/// A solution part, taking the puzzle input either as text or as raw bytes.
+pub enum Part {
+    Text(SolutionFn<str>),
+    Bytes(SolutionFn<[u8]>),
+}
+
+/// Marker for parts taking the input as `&str`.
+pub struct TextInput;
+
+/// Marker for parts taking the input as `&[u8]`.
+pub struct ByteInput;
+
+/// Marker for parts returning a `Result`, the error being reported instead
+/// of the answer.
+pub struct Fallible<M>(PhantomData<M>);
+
+/// Converts a day's `part_1`/`part_2` function into a [`Part`]. Days opt into
+/// the byte API simply by declaring their parts as `fn(&[u8])`, and can
+/// reject invalid inputs by returning a `Result`.
+pub trait IntoPart<Marker> {
+    fn into_part(self) -> Part;
+}
+
+impl<F> IntoPart<TextInput> for F
+where
+    F: Fn(&str) -> Box<dyn Display> + Send + Sync + 'static,
+{
+    fn into_part(self) -> Part {
+        Part::Text(Box::new(move |input| Ok(self(input))))
+    }
+}
+
+impl<F> IntoPart<ByteInput> for F
+where
+    F: Fn(&[u8]) -> Box<dyn Display> + Send + Sync + 'static,
+{
+    fn into_part(self) -> Part {
+        Part::Bytes(Box::new(move |input| Ok(self(input))))
+    }
+}
+
+impl<F, E> IntoPart<Fallible<TextInput>> for F
+where
+    F: Fn(&str) -> Result<Box<dyn Display>, E> + Send + Sync + 'static,
+    E: Display,
+{
+    fn into_part(self) -> Part {
+        Part::Text(Box::new(move |input| {
+            self(input).map_err(|e| e.to_string())
+        }))
+    }
+}
+
+impl<F, E> IntoPart<Fallible<ByteInput>> for F
+where
+    F: Fn(&[u8]) -> Result<Box<dyn Display>, E> + Send + Sync + 'static,
+    E: Display,
+{
+    fn into_part(self) -> Part {
+        Part::Bytes(Box::new(move |input| {
+            self(input).map_err(|e| e.to_string())
+        }))
+    }
+}
 
-#[derive(Clone, Copy)]
 pub struct DaySolution {
-    part_1: SolutionFn,
-    part_2: SolutionFn,
+    part_1: Part,
+    part_2: Part,
+    generator: GeneratorFn,
 }
 
 impl DaySolution {
-    pub fn new(part_1: SolutionFn, part_2: SolutionFn) -> Self {
-        Self { part_1, part_2 }
+    pub fn new<M1, M2>(
+        part_1: impl IntoPart<M1>,
+        part_2: impl IntoPart<M2>,
+        generator: GeneratorFn,
+    ) -> Self {
+        Self {
+            part_1: part_1.into_part(),
+            part_2: part_2.into_part(),
+            generator,
+        }
+    }
+
+    /// Generates a random valid input for the day. The meaning of `size`
+    /// depends on the day, e.g. number of lines or side of a grid.
+    pub fn generate(&self, rng: &mut StdRng, size: usize) -> String {
+        (self.generator)(rng, size)
     }
 
-    pub fn run_parts(&self, input: &str, day: u32) {
+    pub fn run_parts(&self, input: &Input, day: u32) {
         println!(
             "{} {}",
             "─┬─".cyan(),
             format!("{} {:02} {}", "Running day".blue(), day, "solution:".blue()).italic()
         );
 
+        // Text is only validated once, and only if some part asks for it.
+        let text = OnceCell::new();
+        let run = |part: &Part| -> Answer {
+            match part {
+                Part::Text(f) => match text.get_or_init(|| input.as_str()) {
+                    Ok(text) => f(text),
+                    Err(e) => Err(format!("Input is not valid UTF-8: {e}")),
+                },
+                Part::Bytes(f) => f(input.as_bytes()),
+            }
+        };
+
         macro_rules! print_result {
-            ($res:expr, $c:expr) => {
+            ($part:expr, $c:expr) => {
                 let time = Instant::now();
-                println!(
-                    "{} {} {}. {} {:?}",
-                    &format!(" {}", $c).cyan(),
-                    "Answer:".green(),
-                    $res,
-                    "Elapsed time:".yellow(),
-                    time.elapsed()
-                );
+                match run($part) {
+                    Ok(res) => println!(
+                        "{} {} {}. {} {:?}",
+                        &format!(" {}", $c).cyan(),
+                        "Answer:".green(),
+                        res,
+                        "Elapsed time:".yellow(),
+                        time.elapsed()
+                    ),
+                    Err(e) => eprintln!("{} {}", &format!(" {}", $c).cyan(), e.red()),
+                }
             };
         }
 
-        print_result!((self.part_1)(input), "├─");
-        print_result!((self.part_2)(input), "└─");
+        print_result!(&self.part_1, "├─");
+        print_result!(&self.part_2, "└─");
     }
 }
 
@@ -51,9 +152,18 @@ macro_rules! register_days {
             lazy_static! {
                 pub static ref DAY_SOLUTIONS: BTreeMap<u32, DaySolution> = {
                     let mut map = BTreeMap::new();
-                    $(
-                        map.insert($day, DaySolution::new([<day_ $day>]::part_1, [<day_ $day>]::part_2));
-                    )*
+                    // Day numbers are written with a leading zero to match
+                    // the module names.
+                    #[allow(clippy::zero_prefixed_literal)]
+                    {
+                        $(
+                            map.insert($day, DaySolution::new(
+                                [<day_ $day>]::part_1,
+                                [<day_ $day>]::part_2,
+                                [<day_ $day>]::generate,
+                            ));
+                        )*
+                    }
                     map
                 };
             }
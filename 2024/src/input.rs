@@ -0,0 +1,93 @@
+use std::{fs::File, io, ops::Deref, path::Path, str::Utf8Error};
+
+use memmap2::Mmap;
+
+/// Puzzle input, either memory-mapped from a file or owned in memory.
+///
+/// Solutions may take the input as `&str` or as raw `&[u8]`, the latter
+/// skipping UTF-8 validation entirely, which matters for very large inputs.
+#[derive(Debug)]
+pub enum Input {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Input {
+    /// Memory-maps the file at `path`. Its contents are only paged in when
+    /// they're accessed.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the input files are never modified while a solution runs,
+        // so the mapped memory stays valid for the lifetime of the map.
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self::Mapped(mmap))
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Mapped(mmap) => mmap,
+            Self::Owned(data) => data,
+        }
+    }
+
+    /// Validates and returns the input as UTF-8 text.
+    #[inline]
+    pub fn as_str(&self) -> Result<&str, Utf8Error> {
+        std::str::from_utf8(self.as_bytes())
+    }
+}
+
+impl Deref for Input {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_bytes()
+    }
+}
+
+impl From<String> for Input {
+    fn from(s: String) -> Self {
+        Self::Owned(s.into_bytes())
+    }
+}
+
+impl From<&str> for Input {
+    fn from(s: &str) -> Self {
+        Self::Owned(s.as_bytes().to_vec())
+    }
+}
+
+impl From<Vec<u8>> for Input {
+    fn from(data: Vec<u8>) -> Self {
+        Self::Owned(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_open_mapped() {
+        let path = std::env::temp_dir().join(format!("aoc2024_input_{}", std::process::id()));
+        File::create(&path)
+            .and_then(|mut f| f.write_all(b"1 2\n3 4\n"))
+            .unwrap();
+
+        let input = Input::open(&path).unwrap();
+        assert!(matches!(input, Input::Mapped(_)));
+        assert_eq!(input.as_bytes(), b"1 2\n3 4\n");
+        assert_eq!(input.as_str(), Ok("1 2\n3 4\n"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let input = Input::from(vec![b'a', 0xff, b'b']);
+        assert_eq!(input.len(), 3);
+        assert!(input.as_str().is_err());
+    }
+}
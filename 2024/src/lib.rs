@@ -0,0 +1,4 @@
+//! Helpers shared by the days. Not all of them are used by every season, so
+//! they live in a library where unused public items are fine.
+
+pub mod utils;
@@ -1,20 +1,68 @@
-use clap::Parser;
-use std::fs;
+use clap::{Parser, Subcommand};
+use rand::{rngs::StdRng, SeedableRng};
 
 use crate::days::DAY_SOLUTIONS;
+use crate::input::Input;
 use colored::Colorize;
 
 pub mod days;
+pub mod input;
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
     #[arg(short, long)]
     day: Option<u32>,
+
+    /// Animates the day in the terminal instead of solving it
+    #[arg(long, requires = "day")]
+    visualize: bool,
+
+    /// Frames per second of the animation
+    #[arg(
+        long,
+        requires = "visualize",
+        default_value_t = 20,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    fps: u32,
+
+    /// Prints details about the answers under them, for days that support it
+    #[arg(long)]
+    explain: bool,
+
+    /// Prints the day's graph in Graphviz DOT format, optionally restricted
+    /// to one part of the input, e.g. an update number
+    #[arg(long, value_name = "PART", requires = "day")]
+    dot: Option<Option<usize>>,
+
+    /// Steps through the day's simulation interactively
+    #[arg(long, requires = "day", conflicts_with = "visualize")]
+    debug: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Prints a randomly generated input for a day
+    Generate {
+        #[arg(short, long)]
+        day: u32,
+
+        /// Size of the input, e.g. number of lines or side of the grid
+        #[arg(short, long, default_value_t = 1000)]
+        size: usize,
+
+        /// Seed of the generator, a random one is used when omitted
+        #[arg(long)]
+        seed: Option<u64>,
+    },
 }
 
-fn read_day_input(path: &str, day: u32) -> Option<String> {
-    if let Ok(input) = fs::read_to_string(path) {
+fn read_day_input(path: &str, day: u32) -> Option<Input> {
+    if let Ok(input) = Input::open(path) {
         Some(input)
     } else {
         eprintln!(
@@ -25,26 +73,44 @@ fn read_day_input(path: &str, day: u32) -> Option<String> {
     }
 }
 
-fn main() {
-    let cli = Cli::parse();
+fn print_unregistered_day(day: u32) {
+    eprintln!(
+        "{}",
+        format!(
+            "Day {} not registered. Add a {} file to register it.",
+            day,
+            format!("src/days/day_{:02}.rs", day).yellow()
+        )
+        .red()
+    );
+}
+
+/// Prints the day's details about its answers, for days that support it.
+fn explain_day(day: u32, input: &Input) {
+    let report = match (day, input.as_str()) {
+        (3, _) => Ok(days::day_03::explain(input.as_bytes())),
+        (5, Ok(input)) => days::day_05::explain(input),
+        _ => return,
+    };
+    // Invalid inputs were already reported when running the parts.
+    if let Ok(report) = report {
+        print!("{report}");
+    }
+}
 
-    match cli.day {
+fn run_days(day: Option<u32>, explain: bool) {
+    match day {
         Some(day) => {
             if let Some(solution) = DAY_SOLUTIONS.get(&day) {
                 let Some(input) = read_day_input(&format!("input/day_{:02}", day), day) else {
                     return;
                 };
                 solution.run_parts(&input, day);
+                if explain {
+                    explain_day(day, &input);
+                }
             } else {
-                eprintln!(
-                    "{}",
-                    format!(
-                        "Day {} not registered. Add a {} file to register it.",
-                        day,
-                        format!("src/days/day_{:02}.rs", day).yellow()
-                    )
-                    .red()
-                );
+                print_unregistered_day(day);
             }
         }
         None => {
@@ -58,8 +124,75 @@ fn main() {
                     continue;
                 };
                 solution.run_parts(&input, day);
+                if explain {
+                    explain_day(day, &input);
+                }
                 first_run = false;
             }
         }
     }
 }
+
+/// Runs `f` on the day's input as text, for modes that don't go through
+/// [`DaySolution`](days::DaySolution).
+fn with_text_input(day: u32, f: impl FnOnce(&str)) {
+    let Some(input) = read_day_input(&format!("input/day_{:02}", day), day) else {
+        return;
+    };
+    match input.as_str() {
+        Ok(input) => f(input),
+        Err(e) => eprintln!("{}", format!("Input is not valid UTF-8: {e}").red()),
+    }
+}
+
+fn visualize_day(day: u32, fps: u32) {
+    match day {
+        6 => with_text_input(day, |input| days::day_06::visualize(input, fps)),
+        _ => eprintln!("{}", format!("Day {day} has no visualization.").red()),
+    }
+}
+
+fn debug_day(day: u32) {
+    match day {
+        6 => with_text_input(day, days::day_06::debug),
+        _ => eprintln!("{}", format!("Day {day} has no debugger.").red()),
+    }
+}
+
+fn dot_day(day: u32, part: Option<usize>) {
+    let dot: fn(&str, Option<usize>) -> Result<String, String> = match day {
+        5 => days::day_05::dot,
+        _ => return eprintln!("{}", format!("Day {day} has no graph.").red()),
+    };
+    with_text_input(day, |input| match dot(input, part) {
+        Ok(dot) => print!("{dot}"),
+        Err(e) => eprintln!("{}", e.red()),
+    });
+}
+
+fn generate_input(day: u32, size: usize, seed: Option<u64>) {
+    let Some(solution) = DAY_SOLUTIONS.get(&day) else {
+        print_unregistered_day(day);
+        return;
+    };
+
+    let seed = seed.unwrap_or_else(rand::random);
+    eprintln!("{} {}", "Generator seed:".yellow(), seed);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    print!("{}", solution.generate(&mut rng, size));
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Generate { day, size, seed }) => generate_input(day, size, seed),
+        None => match cli.day {
+            Some(day) if cli.visualize => visualize_day(day, cli.fps),
+            Some(day) if cli.debug => debug_day(day),
+            Some(day) if cli.dot.is_some() => dot_day(day, cli.dot.flatten()),
+            day => run_days(day, cli.explain),
+        },
+    }
+}
@@ -0,0 +1,245 @@
+use super::geometry::{Dir4, Point};
+
+/// Fixed-size set of indices below `capacity`, one bit each.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            words: vec![0; capacity.div_ceil(64)],
+            capacity,
+        }
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds `i`, returning whether it wasn't already in the set.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of capacity.
+    #[inline]
+    pub fn insert(&mut self, i: usize) -> bool {
+        assert!(i < self.capacity, "index {i} out of capacity");
+        let (word, bit) = (&mut self.words[i / 64], 1 << (i % 64));
+        let added = *word & bit == 0;
+        *word |= bit;
+        added
+    }
+
+    /// Removes `i`, returning whether it was in the set.
+    #[inline]
+    pub fn remove(&mut self, i: usize) -> bool {
+        let Some(word) = self.words.get_mut(i / 64) else {
+            return false;
+        };
+        let bit = 1 << (i % 64);
+        let removed = *word & bit != 0;
+        *word &= !bit;
+        removed
+    }
+
+    #[inline]
+    pub fn contains(&self, i: usize) -> bool {
+        self.words
+            .get(i / 64)
+            .is_some_and(|w| w & (1 << (i % 64)) != 0)
+    }
+
+    /// Number of indices in the set.
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Indices in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(k, &w)| {
+            let mut w = w;
+            std::iter::from_fn(move || {
+                (w != 0).then(|| {
+                    let bit = w.trailing_zeros() as usize;
+                    w &= w - 1;
+                    k * 64 + bit
+                })
+            })
+        })
+    }
+}
+
+/// Set of cells of a grid with the given bounds, e.g. the visited ones.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GridSet {
+    bits: BitSet,
+    cols: usize,
+}
+
+impl GridSet {
+    pub fn new((rows, cols): (usize, usize)) -> Self {
+        Self {
+            bits: BitSet::new(rows * cols),
+            cols,
+        }
+    }
+
+    #[inline]
+    fn index(&self, Point(i, j): Point) -> usize {
+        assert!(j < self.cols, "column {j} out of bounds");
+        i * self.cols + j
+    }
+
+    #[inline]
+    pub fn insert(&mut self, pos: Point) -> bool {
+        self.bits.insert(self.index(pos))
+    }
+
+    #[inline]
+    pub fn remove(&mut self, pos: Point) -> bool {
+        self.bits.remove(self.index(pos))
+    }
+
+    #[inline]
+    pub fn contains(&self, Point(i, j): Point) -> bool {
+        j < self.cols && self.bits.contains(i * self.cols + j)
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Cells in the set, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
+        self.bits
+            .iter()
+            .map(|k| Point(k / self.cols, k % self.cols))
+    }
+}
+
+/// Set of (cell, heading) states of a grid walk, e.g. to detect loops.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GridDirSet {
+    bits: BitSet,
+    cols: usize,
+}
+
+impl GridDirSet {
+    pub fn new((rows, cols): (usize, usize)) -> Self {
+        Self {
+            bits: BitSet::new(rows * cols * 4),
+            cols,
+        }
+    }
+
+    #[inline]
+    fn index(&self, Point(i, j): Point, dir: Dir4) -> usize {
+        assert!(j < self.cols, "column {j} out of bounds");
+        (i * self.cols + j) * 4 + dir as usize
+    }
+
+    #[inline]
+    pub fn insert(&mut self, pos: Point, dir: Dir4) -> bool {
+        self.bits.insert(self.index(pos, dir))
+    }
+
+    #[inline]
+    pub fn remove(&mut self, pos: Point, dir: Dir4) -> bool {
+        self.bits.remove(self.index(pos, dir))
+    }
+
+    #[inline]
+    pub fn contains(&self, Point(i, j): Point, dir: Dir4) -> bool {
+        j < self.cols && self.bits.contains((i * self.cols + j) * 4 + dir as usize)
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// States in the set, in row-major order then clockwise from up.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, Dir4)> + '_ {
+        self.bits.iter().map(|k| {
+            let cell = k / 4;
+            (Point(cell / self.cols, cell % self.cols), Dir4::ALL[k % 4])
+        })
+    }
+
+    /// Cells visited with any heading.
+    pub fn cells(&self) -> GridSet {
+        let rows = self.bits.capacity() / 4 / self.cols.max(1);
+        let mut set = GridSet::new((rows, self.cols));
+        for (pos, _) in self.iter() {
+            set.insert(pos);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bitset() {
+        let mut bits = BitSet::new(130);
+        assert!(bits.insert(3) && bits.insert(64) && bits.insert(129));
+        assert!(!bits.insert(64));
+        assert!(bits.contains(129) && !bits.contains(128) && !bits.contains(500));
+        assert_eq!(bits.iter().collect::<Vec<_>>(), vec![3, 64, 129]);
+
+        assert!(bits.remove(3) && !bits.remove(3));
+        assert_eq!(bits.count(), 2);
+    }
+
+    #[test]
+    fn test_grid_sets() {
+        let mut cells = GridSet::new((3, 5));
+        cells.insert(Point(2, 4));
+        cells.insert(Point(0, 1));
+        assert!(cells.contains(Point(2, 4)) && !cells.contains(Point(0, 7)));
+        assert_eq!(
+            cells.iter().collect::<Vec<_>>(),
+            vec![Point(0, 1), Point(2, 4)]
+        );
+
+        let mut states = GridDirSet::new((3, 5));
+        assert!(states.insert(Point(1, 1), Dir4::Left));
+        assert!(states.insert(Point(1, 1), Dir4::Up));
+        assert!(!states.insert(Point(1, 1), Dir4::Left));
+        assert!(!states.contains(Point(1, 1), Dir4::Down));
+        assert_eq!(
+            states.iter().collect::<Vec<_>>(),
+            vec![(Point(1, 1), Dir4::Up), (Point(1, 1), Dir4::Left)]
+        );
+        assert_eq!(states.cells().count(), 1);
+    }
+}
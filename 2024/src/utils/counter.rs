@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    ops::{Add, BitAnd, BitOr, Sub},
+};
+
+/// Multiset counting how many times each value was added. Values with a zero
+/// count are never stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter<T: Hash + Eq> {
+    counts: HashMap<T, usize>,
+}
+
+impl<T: Hash + Eq> Default for Counter<T> {
+    fn default() -> Self {
+        Self {
+            counts: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> Counter<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn add(&mut self, value: T) {
+        self.add_n(value, 1);
+    }
+
+    pub fn add_n(&mut self, value: T, n: usize) {
+        if n > 0 {
+            *self.counts.entry(value).or_default() += n;
+        }
+    }
+
+    /// Removes up to `n` occurrences of `value`, returning how many were
+    /// actually removed.
+    pub fn remove_n(&mut self, value: &T, n: usize) -> usize {
+        let Some(count) = self.counts.get_mut(value) else {
+            return 0;
+        };
+        let removed = n.min(*count);
+        *count -= removed;
+        if *count == 0 {
+            self.counts.remove(value);
+        }
+        removed
+    }
+
+    #[inline]
+    pub fn count(&self, value: &T) -> usize {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+
+    /// Number of distinct values.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Sum of all counts.
+    pub fn total(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(v, &c)| (v, c))
+    }
+
+    fn merge_with(mut self, other: Self, f: impl Fn(usize, usize) -> usize) -> Self {
+        let mut counts = HashMap::new();
+        for (v, b) in other.counts {
+            let a = self.counts.remove(&v).unwrap_or(0);
+            counts.insert(v, f(a, b));
+        }
+        for (v, a) in self.counts {
+            counts.insert(v, f(a, 0));
+        }
+        counts.retain(|_, c| *c > 0);
+        Self { counts }
+    }
+}
+
+impl<T: Hash + Eq + Ord> Counter<T> {
+    /// Values from most to least common, ties ordered by value.
+    pub fn most_common(&self) -> Vec<(&T, usize)> {
+        let mut res = self.iter().collect::<Vec<_>>();
+        res.sort_by(|(a, ca), (b, cb)| cb.cmp(ca).then(a.cmp(b)));
+        res
+    }
+}
+
+impl<T: Hash + Eq> FromIterator<T> for Counter<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut counter = Self::new();
+        counter.extend(iter);
+        counter
+    }
+}
+
+impl<T: Hash + Eq> Extend<T> for Counter<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for v in iter {
+            self.add(v);
+        }
+    }
+}
+
+impl<T: Hash + Eq> Add for Counter<T> {
+    type Output = Self;
+
+    /// Sums the counts of both counters.
+    fn add(self, other: Self) -> Self::Output {
+        self.merge_with(other, |a, b| a + b)
+    }
+}
+
+impl<T: Hash + Eq> Sub for Counter<T> {
+    type Output = Self;
+
+    /// Subtracts the counts, dropping values that reach zero.
+    fn sub(self, other: Self) -> Self::Output {
+        self.merge_with(other, usize::saturating_sub)
+    }
+}
+
+impl<T: Hash + Eq> BitAnd for Counter<T> {
+    type Output = Self;
+
+    /// Intersection, keeping the minimum count of each value.
+    fn bitand(self, other: Self) -> Self::Output {
+        self.merge_with(other, usize::min)
+    }
+}
+
+impl<T: Hash + Eq> BitOr for Counter<T> {
+    type Output = Self;
+
+    /// Union, keeping the maximum count of each value.
+    fn bitor(self, other: Self) -> Self::Output {
+        self.merge_with(other, usize::max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut counter: Counter<char> = "abracadabra".chars().collect();
+        assert_eq!(counter.count(&'a'), 5);
+        assert_eq!(counter.count(&'z'), 0);
+        assert_eq!((counter.len(), counter.total()), (5, 11));
+        assert_eq!(
+            counter.most_common(),
+            vec![(&'a', 5), (&'b', 2), (&'r', 2), (&'c', 1), (&'d', 1)]
+        );
+
+        assert_eq!(counter.remove_n(&'c', 3), 1);
+        assert_eq!(counter.len(), 4);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a: Counter<char> = "aaabc".chars().collect();
+        let b: Counter<char> = "abbd".chars().collect();
+
+        assert_eq!(a.clone() + b.clone(), "aaaabbbcd".chars().collect());
+        assert_eq!(a.clone() - b.clone(), "aac".chars().collect());
+        assert_eq!(a.clone() & b.clone(), "ab".chars().collect());
+        assert_eq!(a | b, "aaabbcd".chars().collect());
+    }
+}
@@ -0,0 +1,140 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// Cycle of a simulation: the state after `start` steps is the first one that
+/// repeats, and it repeats every `len` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Cycle {
+    /// Smallest step with the same state as step `n`.
+    #[inline]
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        match n < self.start {
+            true => n,
+            false => self.start + (n - self.start) % self.len,
+        }
+    }
+}
+
+/// Finds the cycle of an endless simulation with Brent's algorithm, storing
+/// no more than two states at a time.
+pub fn brent<S: Eq + Clone>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Find the cycle length by teleporting the tortoise to the hare every
+    // power of two steps.
+    let (mut power, mut len) = (1, 1);
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // Then find its start with two pointers `len` steps apart.
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..len {
+        hare = step(&hare);
+    }
+
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+
+    Cycle {
+        start: cycle_start,
+        len,
+    }
+}
+
+/// State after `n` steps of an endless simulation, skipping whole cycles.
+pub fn nth_state<S: Eq + Clone>(start: S, mut step: impl FnMut(&S) -> S, n: usize) -> S {
+    let cycle = brent(start.clone(), &mut step);
+    (0..cycle.equivalent_step(n)).fold(start, |s, _| step(&s))
+}
+
+/// Every state a simulation went through, and its cycle if it has one.
+#[derive(Debug, Clone)]
+pub struct History<S> {
+    pub states: Vec<S>,
+    pub cycle: Option<Cycle>,
+}
+
+impl<S> History<S> {
+    /// State after `n` steps, `None` if the simulation ended before that.
+    pub fn state_at(&self, n: usize) -> Option<&S> {
+        match self.cycle {
+            Some(cycle) => self.states.get(cycle.equivalent_step(n)),
+            None => self.states.get(n),
+        }
+    }
+}
+
+/// Runs a simulation until it either ends, with `step` returning `None`, or
+/// reaches a state it has already been in.
+pub fn detect<S: Hash + Eq + Clone>(start: S, mut step: impl FnMut(&S) -> Option<S>) -> History<S> {
+    let mut seen = HashMap::from([(start.clone(), 0)]);
+    let mut states = vec![start];
+
+    while let Some(next) = step(states.last().unwrap()) {
+        if let Some(&first) = seen.get(&next) {
+            return History {
+                cycle: Some(Cycle {
+                    start: first,
+                    len: states.len() - first,
+                }),
+                states,
+            };
+        }
+        seen.insert(next.clone(), states.len());
+        states.push(next);
+    }
+
+    History {
+        states,
+        cycle: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 0 -> 5 -> 3, then the powers of 3 modulo 100 repeat every 20 steps.
+    fn step(s: &u64) -> u64 {
+        match *s {
+            0 => 5,
+            5 => 3,
+            s => s * 3 % 100,
+        }
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent(0, step), Cycle { start: 2, len: 20 });
+        assert_eq!(nth_state(0, step, 2 + 20 * 1_000_000_000 + 1), 9);
+    }
+
+    #[test]
+    fn test_detect() {
+        let history = detect(0, |s| Some(step(s)));
+        assert_eq!(history.cycle, Some(Cycle { start: 2, len: 20 }));
+        assert_eq!(history.state_at(2 + 20 * 1_000_000_000), Some(&3));
+
+        let history = detect(10, |&s| (s > 0).then(|| s - 1));
+        assert_eq!(history.cycle, None);
+        assert_eq!(history.states.len(), 11);
+        assert_eq!(history.state_at(11), None);
+    }
+}
@@ -0,0 +1,331 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+/// Position on a grid, `(i, j)` = (row, col).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point(pub usize, pub usize);
+
+/// Displacement between points, `(delta i, delta j)`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Vector(pub isize, pub isize);
+
+impl Point {
+    /// Moves the point by `v`, returning `None` if it leaves the
+    /// `(rows, cols)` bounds.
+    #[inline]
+    pub fn checked_step(self, v: impl Into<Vector>, (rows, cols): (usize, usize)) -> Option<Self> {
+        let Vector(di, dj) = v.into();
+        let i = self.0.checked_add_signed(di).filter(|&i| i < rows)?;
+        let j = self.1.checked_add_signed(dj).filter(|&j| j < cols)?;
+        Some(Self(i, j))
+    }
+
+    #[inline]
+    pub fn manhattan(self, other: Self) -> usize {
+        self.0.abs_diff(other.0) + self.1.abs_diff(other.1)
+    }
+}
+
+impl Vector {
+    /// Rotates 90 degrees clockwise, with rows growing downwards.
+    #[inline]
+    pub fn rotate_right(self) -> Self {
+        Self(self.1, -self.0)
+    }
+
+    /// Rotates 90 degrees counter-clockwise, with rows growing downwards.
+    #[inline]
+    pub fn rotate_left(self) -> Self {
+        Self(-self.1, self.0)
+    }
+
+    /// Mirrors across the horizontal axis, i.e. up becomes down.
+    #[inline]
+    pub fn flip_vertical(self) -> Self {
+        Self(-self.0, self.1)
+    }
+
+    /// Mirrors across the vertical axis, i.e. left becomes right.
+    #[inline]
+    pub fn flip_horizontal(self) -> Self {
+        Self(self.0, -self.1)
+    }
+
+    /// Mirrors across the `\` diagonal.
+    #[inline]
+    pub fn transpose(self) -> Self {
+        Self(self.1, self.0)
+    }
+
+    #[inline]
+    pub fn manhattan(self) -> usize {
+        self.0.unsigned_abs() + self.1.unsigned_abs()
+    }
+}
+
+impl Add<Vector> for Point {
+    type Output = Option<Point>;
+
+    /// Unbounded step, `None` only if a coordinate goes below zero.
+    fn add(self, Vector(di, dj): Vector) -> Self::Output {
+        Some(Point(
+            self.0.checked_add_signed(di)?,
+            self.1.checked_add_signed(dj)?,
+        ))
+    }
+}
+
+impl Sub for Point {
+    type Output = Vector;
+
+    fn sub(self, other: Self) -> Self::Output {
+        Vector(
+            self.0 as isize - other.0 as isize,
+            self.1 as isize - other.1 as isize,
+        )
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Self) -> Self::Output {
+        Self(self.0 + other.0, self.1 + other.1)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<isize> for Vector {
+    type Output = Vector;
+
+    fn mul(self, k: isize) -> Self::Output {
+        Self(self.0 * k, self.1 * k)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0, -self.1)
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((i, j): (usize, usize)) -> Self {
+        Self(i, j)
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(Point(i, j): Point) -> Self {
+        (i, j)
+    }
+}
+
+impl From<(isize, isize)> for Vector {
+    fn from((di, dj): (isize, isize)) -> Self {
+        Self(di, dj)
+    }
+}
+
+impl From<Vector> for (isize, isize) {
+    fn from(Vector(di, dj): Vector) -> Self {
+        (di, dj)
+    }
+}
+
+/// Orthogonal directions, in clockwise order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir4 {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    #[inline]
+    pub fn rotate_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    #[inline]
+    pub fn rotate_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    #[inline]
+    pub fn reverse(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    /// Up becomes down and vice versa.
+    #[inline]
+    pub fn flip_vertical(self) -> Self {
+        match self {
+            Self::Up | Self::Down => self.reverse(),
+            _ => self,
+        }
+    }
+
+    /// Left becomes right and vice versa.
+    #[inline]
+    pub fn flip_horizontal(self) -> Self {
+        match self {
+            Self::Left | Self::Right => self.reverse(),
+            _ => self,
+        }
+    }
+
+    #[inline]
+    pub fn vector(self) -> Vector {
+        match self {
+            Self::Up => Vector(-1, 0),
+            Self::Right => Vector(0, 1),
+            Self::Down => Vector(1, 0),
+            Self::Left => Vector(0, -1),
+        }
+    }
+}
+
+/// Orthogonal and diagonal directions, in clockwise order starting north.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir8 {
+    N,
+    NE,
+    E,
+    SE,
+    S,
+    SW,
+    W,
+    NW,
+}
+
+impl Dir8 {
+    #[rustfmt::skip]
+    pub const ALL: [Self; 8] = [
+        Self::N, Self::NE, Self::E, Self::SE,
+        Self::S, Self::SW, Self::W, Self::NW,
+    ];
+
+    /// Rotates 45 degrees clockwise.
+    #[inline]
+    pub fn rotate_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    /// Rotates 45 degrees counter-clockwise.
+    #[inline]
+    pub fn rotate_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    #[inline]
+    pub fn reverse(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    /// North becomes south and vice versa.
+    #[inline]
+    pub fn flip_vertical(self) -> Self {
+        Self::ALL[(12 - self as usize) % 8]
+    }
+
+    /// East becomes west and vice versa.
+    #[inline]
+    pub fn flip_horizontal(self) -> Self {
+        Self::ALL[(8 - self as usize) % 8]
+    }
+
+    #[inline]
+    pub fn vector(self) -> Vector {
+        match self {
+            Self::N => Vector(-1, 0),
+            Self::NE => Vector(-1, 1),
+            Self::E => Vector(0, 1),
+            Self::SE => Vector(1, 1),
+            Self::S => Vector(1, 0),
+            Self::SW => Vector(1, -1),
+            Self::W => Vector(0, -1),
+            Self::NW => Vector(-1, -1),
+        }
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(d: Dir4) -> Self {
+        Self::ALL[d as usize * 2]
+    }
+}
+
+impl From<Dir4> for Vector {
+    fn from(d: Dir4) -> Self {
+        d.vector()
+    }
+}
+
+impl From<Dir8> for Vector {
+    fn from(d: Dir8) -> Self {
+        d.vector()
+    }
+}
+
+impl From<Dir4> for (isize, isize) {
+    fn from(d: Dir4) -> Self {
+        d.vector().into()
+    }
+}
+
+impl From<Dir8> for (isize, isize) {
+    fn from(d: Dir8) -> Self {
+        d.vector().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotations() {
+        for d in Dir4::ALL {
+            assert_eq!(d.rotate_right().vector(), d.vector().rotate_right());
+            assert_eq!(d.rotate_left().vector(), d.vector().rotate_left());
+            assert_eq!(d.flip_vertical().vector(), d.vector().flip_vertical());
+            assert_eq!(d.flip_horizontal().vector(), d.vector().flip_horizontal());
+            assert_eq!(Dir8::from(d).vector(), d.vector());
+        }
+        for d in Dir8::ALL {
+            assert_eq!(d.rotate_right().rotate_left(), d);
+            assert_eq!(
+                d.rotate_right().rotate_right().vector(),
+                d.vector().rotate_right()
+            );
+            assert_eq!(d.reverse().vector(), -d.vector());
+            assert_eq!(d.flip_vertical().vector(), d.vector().flip_vertical());
+            assert_eq!(d.flip_horizontal().vector(), d.vector().flip_horizontal());
+        }
+    }
+
+    #[test]
+    fn test_stepping() {
+        let bounds = (3, 3);
+        assert_eq!(Point(0, 0).checked_step(Dir4::Up, bounds), None);
+        assert_eq!(Point(0, 2).checked_step(Dir4::Right, bounds), None);
+        assert_eq!(
+            Point(1, 1).checked_step(Dir8::SE, bounds),
+            Some(Point(2, 2))
+        );
+        assert_eq!(Point(1, 1) + Vector(-2, 0), None);
+        assert_eq!(Point(4, 0) - Point(1, 2), Vector(3, -2));
+        assert_eq!(Point(4, 0).manhattan(Point(1, 2)), 5);
+        assert_eq!((Vector(1, -2) * 3).manhattan(), 9);
+    }
+}
@@ -0,0 +1,388 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Debug, Display, Write},
+    hash::Hash,
+};
+
+/// Directed graph with adjacency lists. Nodes are stored once and referred
+/// to by their insertion index internally.
+#[derive(Debug, Clone)]
+pub struct DiGraph<N> {
+    nodes: Vec<N>,
+    index: HashMap<N, usize>,
+    adj: Vec<Vec<usize>>,
+}
+
+/// Cycle found while sorting a graph, every node has an edge to the next one
+/// and the last node has an edge back to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError<N>(pub Vec<N>);
+
+impl<N: Debug> Display for CycleError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph has a cycle: ")?;
+        for n in &self.0 {
+            write!(f, "{n:?} -> ")?;
+        }
+        match self.0.first() {
+            Some(n) => write!(f, "{n:?}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<N: Debug> std::error::Error for CycleError<N> {}
+
+impl<N> Default for DiGraph<N> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            index: HashMap::new(),
+            adj: vec![],
+        }
+    }
+}
+
+impl<N: Hash + Eq + Clone> DiGraph<N> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `n` if it isn't in the graph yet, returning its index.
+    pub fn add_node(&mut self, n: N) -> usize {
+        if let Some(&i) = self.index.get(&n) {
+            return i;
+        }
+        self.nodes.push(n.clone());
+        self.adj.push(vec![]);
+        self.index.insert(n, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Adds the edge `a -> b`, along with any missing node. Duplicate edges
+    /// are ignored.
+    pub fn add_edge(&mut self, a: N, b: N) {
+        let (a, b) = (self.add_node(a), self.add_node(b));
+        if !self.adj[a].contains(&b) {
+            self.adj[a].push(b);
+        }
+    }
+
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.adj.iter().map(Vec::len).sum()
+    }
+
+    #[inline]
+    pub fn contains(&self, n: &N) -> bool {
+        self.index.contains_key(n)
+    }
+
+    pub fn has_edge(&self, a: &N, b: &N) -> bool {
+        match (self.index.get(a), self.index.get(b)) {
+            (Some(&a), Some(b)) => self.adj[a].contains(b),
+            _ => false,
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.iter()
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (&N, &N)> {
+        self.adj
+            .iter()
+            .enumerate()
+            .flat_map(move |(a, succ)| succ.iter().map(move |&b| (&self.nodes[a], &self.nodes[b])))
+    }
+
+    pub fn successors(&self, n: &N) -> impl Iterator<Item = &N> {
+        self.index
+            .get(n)
+            .into_iter()
+            .flat_map(move |&i| self.adj[i].iter().map(|&j| &self.nodes[j]))
+    }
+
+    /// Subgraph with only `nodes` and the edges between them. Nodes missing
+    /// from the graph are added without edges.
+    pub fn induced_subgraph<'a>(&self, nodes: impl IntoIterator<Item = &'a N>) -> Self
+    where
+        N: 'a,
+    {
+        let mut sub = Self::new();
+        for n in nodes {
+            sub.add_node(n.clone());
+        }
+        for (i, n) in sub.nodes.iter().enumerate() {
+            for m in self.successors(n) {
+                if let Some(&j) = sub.index.get(m) {
+                    sub.adj[i].push(j);
+                }
+            }
+        }
+        sub
+    }
+
+    /// Orders the nodes so that every edge goes forward. Ties are broken by
+    /// insertion order, so the result is deterministic.
+    pub fn topo_sort(&self) -> Result<Vec<N>, CycleError<N>> {
+        let mut in_degree = vec![0; self.nodes.len()];
+        for &j in self.adj.iter().flatten() {
+            in_degree[j] += 1;
+        }
+
+        let mut queue = (0..self.nodes.len())
+            .filter(|&i| in_degree[i] == 0)
+            .collect::<VecDeque<_>>();
+        let mut order = Vec::with_capacity(self.nodes.len());
+
+        while let Some(i) = queue.pop_front() {
+            order.push(self.nodes[i].clone());
+            for &j in &self.adj[i] {
+                in_degree[j] -= 1;
+                if in_degree[j] == 0 {
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        match order.len() == self.nodes.len() {
+            true => Ok(order),
+            false => Err(CycleError(self.find_cycle().unwrap())),
+        }
+    }
+
+    /// Topological order of `nodes` considering only the edges between them.
+    pub fn topo_sort_subset<'a>(
+        &self,
+        nodes: impl IntoIterator<Item = &'a N>,
+    ) -> Result<Vec<N>, CycleError<N>>
+    where
+        N: 'a,
+    {
+        self.induced_subgraph(nodes).topo_sort()
+    }
+
+    /// Returns any cycle of the graph, in edge order.
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            OnStack,
+            Done,
+        }
+
+        let mut state = vec![State::New; self.nodes.len()];
+
+        for root in 0..self.nodes.len() {
+            if state[root] != State::New {
+                continue;
+            }
+
+            // Iterative DFS, each frame holds a node and its next edge.
+            let mut stack = vec![(root, 0)];
+            state[root] = State::OnStack;
+
+            while let Some((i, edge)) = stack.last_mut() {
+                let i = *i;
+                let Some(&j) = self.adj[i].get(*edge) else {
+                    state[i] = State::Done;
+                    stack.pop();
+                    continue;
+                };
+                *edge += 1;
+
+                match state[j] {
+                    State::New => {
+                        state[j] = State::OnStack;
+                        stack.push((j, 0));
+                    }
+                    State::OnStack => {
+                        let start = stack.iter().position(|&(k, _)| k == j).unwrap();
+                        return Some(
+                            stack[start..]
+                                .iter()
+                                .map(|&(k, _)| self.nodes[k].clone())
+                                .collect(),
+                        );
+                    }
+                    State::Done => (),
+                }
+            }
+        }
+        None
+    }
+
+    /// Strongly connected components, using Kosaraju's algorithm. Components
+    /// come out in topological order of the condensed graph.
+    pub fn sccs(&self) -> Vec<Vec<N>> {
+        let n = self.nodes.len();
+
+        // First pass, nodes ordered by DFS finish time.
+        let mut visited = vec![false; n];
+        let mut finished = Vec::with_capacity(n);
+        for root in 0..n {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            let mut stack = vec![(root, 0)];
+
+            while let Some((i, edge)) = stack.last_mut() {
+                let i = *i;
+                match self.adj[i].get(*edge) {
+                    Some(&j) => {
+                        *edge += 1;
+                        if !visited[j] {
+                            visited[j] = true;
+                            stack.push((j, 0));
+                        }
+                    }
+                    None => {
+                        finished.push(i);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        // Second pass on the transposed graph, in reverse finish order.
+        let mut rev = vec![vec![]; n];
+        for (i, succ) in self.adj.iter().enumerate() {
+            for &j in succ {
+                rev[j].push(i);
+            }
+        }
+
+        let mut assigned = vec![false; n];
+        let mut components = vec![];
+        for &root in finished.iter().rev() {
+            if assigned[root] {
+                continue;
+            }
+            assigned[root] = true;
+            let mut stack = vec![root];
+            let mut component = vec![];
+
+            while let Some(i) = stack.pop() {
+                component.push(self.nodes[i].clone());
+                for &j in &rev[i] {
+                    if !assigned[j] {
+                        assigned[j] = true;
+                        stack.push(j);
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Every node reachable from `from` through one or more edges, `from`
+    /// itself is only included if it's part of a cycle.
+    pub fn reachable(&self, from: &N) -> HashSet<N> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = self
+            .index
+            .get(from)
+            .map_or(vec![], |&i| self.adj[i].clone());
+
+        while let Some(i) = stack.pop() {
+            if !std::mem::replace(&mut seen[i], true) {
+                stack.extend(&self.adj[i]);
+            }
+        }
+
+        seen.iter()
+            .enumerate()
+            .filter(|&(_, &s)| s)
+            .map(|(i, _)| self.nodes[i].clone())
+            .collect()
+    }
+
+    #[inline]
+    pub fn is_reachable(&self, from: &N, to: &N) -> bool {
+        self.reachable(from).contains(to)
+    }
+}
+
+impl<N: Hash + Eq + Clone + Display> DiGraph<N> {
+    /// Graphviz DOT description of the graph. `edge_attrs` gives the extra
+    /// attributes of each edge, e.g. `color=red`.
+    pub fn to_dot(&self, mut edge_attrs: impl FnMut(&N, &N) -> Option<String>) -> String {
+        let id = |n: &N| format!("\"{}\"", n.to_string().replace('"', "\\\""));
+
+        let mut dot = String::from("digraph {\n");
+        for n in &self.nodes {
+            let _ = writeln!(dot, "    {};", id(n));
+        }
+        for (a, b) in self.edges() {
+            let _ = match edge_attrs(a, b) {
+                Some(attrs) => writeln!(dot, "    {} -> {} [{attrs}];", id(a), id(b)),
+                None => writeln!(dot, "    {} -> {};", id(a), id(b)),
+            };
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<N: Hash + Eq + Clone> FromIterator<(N, N)> for DiGraph<N> {
+    fn from_iter<I: IntoIterator<Item = (N, N)>>(iter: I) -> Self {
+        let mut graph = Self::new();
+        for (a, b) in iter {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topo_sort() {
+        let graph: DiGraph<u32> = [(97, 75), (75, 47), (47, 61), (97, 61), (75, 61)]
+            .into_iter()
+            .collect();
+        assert_eq!(graph.topo_sort(), Ok(vec![97, 75, 47, 61]));
+        assert_eq!(graph.topo_sort_subset(&[61, 97, 13]), Ok(vec![97, 13, 61]));
+
+        let cyclic: DiGraph<u32> = [(1, 2), (2, 3), (3, 1), (3, 4)].into_iter().collect();
+        assert_eq!(cyclic.topo_sort(), Err(CycleError(vec![1, 2, 3])));
+        // Dropping a node of the cycle makes the induced subgraph sortable.
+        assert_eq!(cyclic.topo_sort_subset(&[4, 3, 1]), Ok(vec![3, 1, 4]));
+    }
+
+    #[test]
+    fn test_sccs_and_reachability() {
+        let graph: DiGraph<char> = [('a', 'b'), ('b', 'a'), ('b', 'c'), ('c', 'd'), ('d', 'c')]
+            .into_iter()
+            .collect();
+
+        let mut sccs = graph.sccs();
+        sccs.iter_mut().for_each(|c| c.sort());
+        assert_eq!(sccs, vec![vec!['a', 'b'], vec!['c', 'd']]);
+
+        assert!(graph.is_reachable(&'a', &'d'));
+        assert!(!graph.is_reachable(&'c', &'a'));
+        assert_eq!(graph.reachable(&'c'), HashSet::from(['c', 'd']));
+        assert!(graph.reachable(&'x').is_empty());
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let graph: DiGraph<&str> = [("a", "b"), ("b", "say \"hi\"")].into_iter().collect();
+        let dot = graph.to_dot(|a, _| (*a == "b").then(|| "color=red".to_owned()));
+        assert_eq!(
+            dot,
+            "digraph {\n    \"a\";\n    \"b\";\n    \"say \\\"hi\\\"\";\n    \
+             \"a\" -> \"b\";\n    \"b\" -> \"say \\\"hi\\\"\" [color=red];\n}\n"
+        );
+    }
+}
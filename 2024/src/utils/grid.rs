@@ -0,0 +1,345 @@
+use std::{
+    fmt::{self, Display},
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use super::geometry::{Dir4, Dir8, Point, Vector};
+
+/// Dense 2D grid stored in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    cols: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseGridError {
+    /// Row at the given line has a different width than the first one.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Character at the given line and column has no cell mapping.
+    InvalidCell { line: usize, col: usize, ch: char },
+}
+
+impl Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line}: expected {expected} columns, found {found}"),
+            Self::InvalidCell { line, col, ch } => {
+                write!(f, "line {line}, column {col}: invalid cell {ch:?}")
+            }
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from row-major `data`. Panics if `data` can't be split
+    /// into rows of `cols` elements.
+    pub fn new(data: Vec<T>, cols: usize) -> Self {
+        assert!(
+            data.len().is_multiple_of(cols),
+            "grid data doesn't fit in rows of {cols} columns"
+        );
+        Self { data, cols }
+    }
+
+    pub fn from_fn(rows: usize, cols: usize, mut f: impl FnMut(Point) -> T) -> Self {
+        let data = (0..rows)
+            .flat_map(|i| (0..cols).map(move |j| Point(i, j)))
+            .map(&mut f)
+            .collect();
+        Self { data, cols }
+    }
+
+    /// Parses a grid mapping each character through `f`, one row per
+    /// non-empty line. Returning `None` from `f` rejects the character.
+    pub fn parse_with(
+        input: &str,
+        mut f: impl FnMut(char) -> Option<T>,
+    ) -> Result<Self, ParseGridError> {
+        let mut data = vec![];
+        let mut cols = None;
+
+        for (line, row) in input.lines().map(str::trim).enumerate() {
+            if row.is_empty() {
+                continue;
+            }
+
+            let start = data.len();
+            for (col, ch) in row.chars().enumerate() {
+                data.push(f(ch).ok_or(ParseGridError::InvalidCell {
+                    line: line + 1,
+                    col: col + 1,
+                    ch,
+                })?);
+            }
+
+            let found = data.len() - start;
+            match cols {
+                Some(expected) if expected != found => {
+                    return Err(ParseGridError::Ragged {
+                        line: line + 1,
+                        expected,
+                        found,
+                    })
+                }
+                _ => cols = Some(found),
+            }
+        }
+
+        Ok(Self {
+            data,
+            cols: cols.unwrap_or(0),
+        })
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        self.data.len().checked_div(self.cols).unwrap_or(0)
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// `(rows, cols)` of the grid, as used by [`Point::checked_step`].
+    #[inline]
+    pub fn bounds(&self) -> (usize, usize) {
+        (self.rows(), self.cols())
+    }
+
+    #[inline]
+    pub fn in_bounds(&self, Point(i, j): Point) -> bool {
+        i < self.rows() && j < self.cols()
+    }
+
+    #[inline]
+    pub fn get(&self, pos: Point) -> Option<&T> {
+        self.in_bounds(pos).then(|| &self[pos])
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, pos: Point) -> Option<&mut T> {
+        self.in_bounds(pos).then(|| &mut self[pos])
+    }
+
+    /// Moves `pos` by `d`, returning `None` if it leaves the grid.
+    #[inline]
+    pub fn offset(&self, pos: Point, d: impl Into<Vector>) -> Option<Point> {
+        pos.checked_step(d, self.bounds())
+    }
+
+    /// Orthogonal neighbors of `pos` inside the grid, clockwise from up.
+    pub fn neighbors4(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Dir4::ALL
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Orthogonal and diagonal neighbors of `pos` inside the grid, clockwise
+    /// from north.
+    pub fn neighbors8(&self, pos: Point) -> impl Iterator<Item = Point> + '_ {
+        Dir8::ALL
+            .into_iter()
+            .filter_map(move |d| self.offset(pos, d))
+    }
+
+    /// Positions from `start` (inclusive) walking in direction `d` until the
+    /// edge of the grid.
+    pub fn ray(&self, start: Point, d: impl Into<Vector>) -> impl Iterator<Item = Point> + '_ {
+        let d = d.into();
+        std::iter::successors(self.in_bounds(start).then_some(start), move |&p| {
+            self.offset(p, d)
+        })
+    }
+
+    /// Every position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Point> {
+        let cols = self.cols;
+        (0..self.rows()).flat_map(move |i| (0..cols).map(move |j| Point(i, j)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+
+    /// Every cell along with its position, in row-major order.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.positions().zip(self.data.iter())
+    }
+
+    #[inline]
+    pub fn row(&self, i: usize) -> &[T] {
+        &self[i]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(self.cols.max(1))
+    }
+
+    pub fn col(&self, j: usize) -> impl Iterator<Item = &T> {
+        self.data.iter().skip(j).step_by(self.cols.max(1))
+    }
+
+    /// Cells on the `\` diagonal starting at `start`.
+    pub fn diagonal(&self, start: Point) -> impl Iterator<Item = &T> {
+        self.ray(start, Dir8::SE).map(|p| &self[p])
+    }
+
+    /// Cells on the `/` diagonal starting at `start`.
+    pub fn anti_diagonal(&self, start: Point) -> impl Iterator<Item = &T> {
+        self.ray(start, Dir8::SW).map(|p| &self[p])
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            data: self.data.iter().map(f).collect(),
+            cols: self.cols,
+        }
+    }
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Position of the first cell equal to `value`, in row-major order.
+    pub fn find(&self, value: &T) -> Option<Point> {
+        self.enumerate().find(|(_, v)| *v == value).map(|(p, _)| p)
+    }
+
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a {
+        self.enumerate()
+            .filter(move |(_, v)| *v == value)
+            .map(|(p, _)| p)
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self {
+        Self {
+            data: vec![value; rows * cols],
+            cols,
+        }
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, Some)
+    }
+}
+
+impl<T> Index<usize> for Grid<T> {
+    type Output = [T];
+
+    fn index(&self, row: usize) -> &Self::Output {
+        let start = row * self.cols;
+        let end = start + self.cols;
+        &self.data[start..end]
+    }
+}
+
+impl<T> IndexMut<usize> for Grid<T> {
+    fn index_mut(&mut self, row: usize) -> &mut Self::Output {
+        let start = row * self.cols;
+        let end = start + self.cols;
+        &mut self.data[start..end]
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, Point(i, j): Point) -> &Self::Output {
+        assert!(j < self.cols, "column {j} out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    #[inline]
+    fn index_mut(&mut self, Point(i, j): Point) -> &mut Self::Output {
+        assert!(j < self.cols, "column {j} out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.iter_rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const TEST_INPUT: &str = indoc! { r#"
+        abc
+        def
+    "# };
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid: Grid<char> = TEST_INPUT.parse().unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid[Point(1, 2)], 'f');
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.col(1).collect::<String>(), "be");
+        assert_eq!(grid.diagonal(Point(0, 0)).collect::<String>(), "ae");
+        assert_eq!(grid.anti_diagonal(Point(0, 2)).collect::<String>(), "ce");
+        assert_eq!(grid.find(&'e'), Some(Point(1, 1)));
+        assert_eq!(grid.to_string(), TEST_INPUT);
+    }
+
+    #[test]
+    fn test_bounds() {
+        let grid: Grid<char> = TEST_INPUT.parse().unwrap();
+        assert_eq!(grid.offset(Point(0, 0), Dir4::Up), None);
+        assert_eq!(grid.offset(Point(0, 2), Dir4::Right), None);
+        assert_eq!(grid.offset(Point(0, 2), Dir8::SW), Some(Point(1, 1)));
+        assert_eq!(grid.get(Point(2, 0)), None);
+        assert_eq!(
+            grid.neighbors4(Point(0, 0)).collect::<Vec<_>>(),
+            vec![Point(0, 1), Point(1, 0)]
+        );
+        assert_eq!(grid.neighbors8(Point(1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "ab\nabc\n".parse::<Grid<char>>(),
+            Err(ParseGridError::Ragged {
+                line: 2,
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Grid::parse_with("..\n.x\n", |c| (c == '.').then_some(())),
+            Err(ParseGridError::InvalidCell {
+                line: 2,
+                col: 2,
+                ch: 'x'
+            })
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::fmt::{self, Display};
+
+/// Greatest common divisor. Unsigned since `gcd(i64::MIN, 0)` is `2^63`.
+pub fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, `None` if it doesn't fit in an `i64`.
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    match (a, b) {
+        (0, _) | (_, 0) => Some(0),
+        _ => {
+            let g = i64::try_from(gcd(a, b)).ok()?;
+            (a / g).checked_mul(b)?.checked_abs()
+        }
+    }
+}
+
+/// Least common multiple of every value, e.g. the period of several cycles.
+pub fn lcm_all(values: impl IntoIterator<Item = i64>) -> Option<i64> {
+    values.into_iter().try_fold(1, lcm)
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y = g = gcd(a, b)`. The gcd
+/// doesn't fit when `a` and `b` are both `i64::MIN` or zero, `g` is then
+/// `i64::MIN`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_s, mut s) = (1i128, 0i128);
+    let (mut old_t, mut t) = (0i128, 1i128);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+
+    match old_r < 0 {
+        true => (-old_r as i64, -old_s as i64, -old_t as i64),
+        false => (old_r as i64, old_s as i64, old_t as i64),
+    }
+}
+
+/// Inverse of `a` modulo `m`, in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Solves the system `x = r (mod m)` for every `(r, m)` with the Chinese
+/// remainder theorem. Moduli don't need to be coprime. Returns `(x, M)` with
+/// `x` in `0..M`, where `M` is the lcm of the moduli, or `None` if the
+/// congruences contradict each other.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    congruences
+        .into_iter()
+        .try_fold((0i64, 1i64), |(x, m), (r, n)| {
+            let (g, p, _) = extended_gcd(m, n);
+            let diff = r - x;
+            if diff % g != 0 {
+                return None;
+            }
+
+            let lcm = lcm(m, n)?;
+            // x + m * k = r (mod n), with k = diff / g * p (mod n / g).
+            let k = (diff / g) as i128 * p as i128 % (n / g) as i128;
+            let res = (x as i128 + m as i128 * k).rem_euclid(lcm as i128);
+            Some((res as i64, lcm))
+        })
+}
+
+/// Ceiling of the square root.
+pub fn isqrt_ceil(n: u64) -> u64 {
+    let r = n.isqrt();
+    match r * r == n {
+        true => r,
+        false => r + 1,
+    }
+}
+
+/// Square root of `n` if it's a perfect square.
+pub fn exact_sqrt(n: u64) -> Option<u64> {
+    let r = n.isqrt();
+    (r * r == n).then_some(r)
+}
+
+/// Number of decimal digits, with zero having one digit.
+#[inline]
+pub fn num_digits(n: u64) -> u32 {
+    n.checked_ilog10().unwrap_or(0) + 1
+}
+
+/// Decimal digits, most significant first.
+pub fn digits(n: u64) -> Vec<u8> {
+    let mut res = vec![];
+    let mut n = n;
+    loop {
+        res.push((n % 10) as u8);
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    res.reverse();
+    res
+}
+
+/// Number from its decimal digits, `None` on overflow.
+pub fn from_digits(digits: &[u8]) -> Option<u64> {
+    digits
+        .iter()
+        .try_fold(0u64, |n, &d| n.checked_mul(10)?.checked_add(d as u64))
+}
+
+/// Appends the digits of `b` to `a`, e.g. `concat(12, 345) = 12345`.
+pub fn concat(a: u64, b: u64) -> Option<u64> {
+    a.checked_mul(10u64.checked_pow(num_digits(b))?)?
+        .checked_add(b)
+}
+
+/// Splits the digits of `n` in two, the right half having `at` digits,
+/// e.g. `split_digits(123456, 2) = (1234, 56)`.
+pub fn split_digits(n: u64, at: u32) -> (u64, u64) {
+    match 10u64.checked_pow(at) {
+        Some(p) => (n / p, n % p),
+        None => (0, n),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowError;
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arithmetic overflow")
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+/// Integers with overflow-checked addition and multiplication.
+pub trait Checked: Copy {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked {
+    ($($t:ty),*) => {
+        $(
+            impl Checked for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                #[inline]
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+
+                #[inline]
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(i32, i64, i128, isize, u32, u64, u128, usize);
+
+/// Sum of every value, failing instead of wrapping on overflow.
+pub fn checked_sum<T: Checked>(values: impl IntoIterator<Item = T>) -> Result<T, OverflowError> {
+    values
+        .into_iter()
+        .try_fold(T::ZERO, |acc, v| acc.checked_add(v).ok_or(OverflowError))
+}
+
+/// Product of every value, failing instead of wrapping on overflow.
+pub fn checked_product<T: Checked>(
+    values: impl IntoIterator<Item = T>,
+) -> Result<T, OverflowError> {
+    values
+        .into_iter()
+        .try_fold(T::ONE, |acc, v| acc.checked_mul(v).ok_or(OverflowError))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(lcm_all([2, 3, 4, 5]), Some(60));
+        assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+        assert_eq!(gcd(i64::MIN, 0), 1 << 63);
+        assert_eq!(lcm(i64::MIN, 1), None);
+        assert_eq!(lcm(i64::MIN, -2), None);
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(
+            (isqrt_ceil(10), exact_sqrt(49), exact_sqrt(50)),
+            (4, Some(7), None)
+        );
+        assert_eq!(digits(1203), vec![1, 2, 0, 3]);
+        assert_eq!(concat(12, 345), Some(12345));
+        assert_eq!(split_digits(123456, 2), (1234, 56));
+        assert_eq!(checked_product([i64::MAX, 2]), Err(OverflowError));
+        assert_eq!(checked_sum([1u32, 2, 3]), Ok(6));
+    }
+
+    fn arb_i64() -> impl Strategy<Value = i64> {
+        prop_oneof![Just(i64::MIN), Just(i64::MAX), -1_000_000i64..1_000_000,]
+    }
+
+    proptest! {
+        #[test]
+        fn prop_gcd_lcm(a in arb_i64(), b in arb_i64()) {
+            let (a, b, g) = (a as i128, b as i128, gcd(a, b) as i128);
+            let lcm_ab = match g {
+                0 => 0,
+                _ => {
+                    prop_assert_eq!((a % g, b % g), (0, 0));
+                    prop_assert_eq!(gcd((a / g) as i64, (b / g) as i64), 1);
+                    (a * b).abs() / g
+                }
+            };
+            prop_assert_eq!(lcm(a as i64, b as i64), i64::try_from(lcm_ab).ok());
+
+            let (g2, x, y) = extended_gcd(a as i64, b as i64);
+            if g <= i64::MAX as i128 {
+                prop_assert_eq!(g2 as i128, g);
+                prop_assert_eq!(a * x as i128 + b * y as i128, g);
+            }
+        }
+
+        #[test]
+        fn prop_mod_inverse(a in any::<i64>(), m in 2i64..1_000_000_007) {
+            match mod_inverse(a, m) {
+                Some(inv) => {
+                    prop_assert!((0..m).contains(&inv));
+                    prop_assert_eq!((a as i128 * inv as i128).rem_euclid(m as i128), 1);
+                }
+                None => prop_assert_ne!(gcd(a, m), 1),
+            }
+        }
+
+        #[test]
+        fn prop_crt(x in 0i64..1_000_000_000, moduli in prop::collection::vec(1i64..1000, 1..5)) {
+            let (res, m) = crt(moduli.iter().map(|&n| (x % n, n))).unwrap();
+            prop_assert_eq!(Some(m), lcm_all(moduli.iter().copied()));
+            prop_assert_eq!(res, x % m);
+        }
+
+        #[test]
+        fn prop_sqrt(n in any::<u32>()) {
+            let n = n as u64;
+            let c = isqrt_ceil(n);
+            prop_assert!(c * c >= n && (c == 0 || (c - 1) * (c - 1) < n));
+            prop_assert_eq!(exact_sqrt(n * n), Some(n));
+        }
+
+        #[test]
+        fn prop_digits(n in any::<u64>(), m in 0u64..1_000_000) {
+            let d = digits(n);
+            prop_assert_eq!(d.len() as u32, num_digits(n));
+            prop_assert_eq!(from_digits(&d), Some(n));
+
+            if let Some(c) = concat(m, n % 1_000_000) {
+                prop_assert_eq!(split_digits(c, num_digits(n % 1_000_000)), (m, n % 1_000_000));
+            }
+        }
+
+        #[test]
+        fn prop_checked(values in prop::collection::vec(any::<i32>(), 0..10)) {
+            // Every partial result has to fit, not only the final one.
+            let fold = |init: i128, f: fn(i128, i128) -> i128| {
+                values.iter().try_fold(init, |acc, &v| {
+                    let res = f(acc, v as i128);
+                    i32::try_from(res).is_ok().then_some(res)
+                })
+            };
+
+            let sum = fold(0, |a, b| a + b).map(|s| s as i32);
+            prop_assert_eq!(checked_sum(values.iter().copied()).ok(), sum);
+
+            let product = fold(1, |a, b| a * b).map(|p| p as i32);
+            prop_assert_eq!(checked_product(values.iter().copied()).ok(), product);
+        }
+    }
+}
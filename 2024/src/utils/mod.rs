@@ -0,0 +1,12 @@
+pub mod bitset;
+pub mod counter;
+pub mod cycle;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod math;
+pub mod parse;
+pub mod ranges;
+pub mod scan;
+pub mod search;
+pub mod term;
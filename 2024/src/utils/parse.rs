@@ -0,0 +1,251 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Parsing error with the 1-based line and column where it happened.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub col: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidValue(String),
+    FieldCount { expected: usize, found: usize },
+}
+
+impl ParseError {
+    fn new(col: usize, kind: ParseErrorKind) -> Self {
+        Self { line: 1, col, kind }
+    }
+
+    /// Moves the error `n` lines down, for errors found in a sub-slice of
+    /// the input.
+    pub fn shifted(self, n: usize) -> Self {
+        Self {
+            line: self.line + n,
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.col)?;
+        match &self.kind {
+            ParseErrorKind::InvalidValue(v) => write!(f, "invalid value {v:?}"),
+            ParseErrorKind::FieldCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Block of consecutive non-blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    /// 1-based line number of the first line of the paragraph.
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl Paragraph<'_> {
+    /// Parses every non-empty line with `f`, locating errors in the whole
+    /// input rather than in the paragraph.
+    pub fn parse_lines<T>(
+        &self,
+        f: impl FnMut(&str) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        parse_lines(self.text, f).map_err(|e| e.shifted(self.line - 1))
+    }
+}
+
+/// Splits `input` on blank lines.
+pub fn paragraphs(input: &str) -> impl Iterator<Item = Paragraph<'_>> {
+    let mut lines = input.split_inclusive('\n').enumerate().peekable();
+
+    std::iter::from_fn(move || {
+        while lines.next_if(|(_, l)| l.trim().is_empty()).is_some() {}
+        let &(first, start) = lines.peek()?;
+
+        let mut len = 0;
+        while let Some((_, l)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            len += l.len();
+        }
+
+        // Lines are contiguous slices of `input`, so the paragraph is too.
+        let offset = start.as_ptr() as usize - input.as_ptr() as usize;
+        Some(Paragraph {
+            line: first + 1,
+            text: input[offset..offset + len].trim_end(),
+        })
+    })
+}
+
+/// Parses every non-empty line of `input` with `f`, filling in the line
+/// number of errors.
+pub fn parse_lines<T>(
+    input: &str,
+    mut f: impl FnMut(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| f(l).map_err(|e| e.shifted(i)))
+        .collect()
+}
+
+/// Column of `field` inside `line`, both must come from the same string.
+#[inline]
+fn col_of(line: &str, field: &str) -> usize {
+    field.as_ptr() as usize - line.as_ptr() as usize + 1
+}
+
+fn parse_field<T: FromStr>(line: &str, field: &str) -> Result<T, ParseError> {
+    field.parse().map_err(|_| {
+        ParseError::new(
+            col_of(line, field),
+            ParseErrorKind::InvalidValue(field.to_owned()),
+        )
+    })
+}
+
+/// Fields of `line` split by `delim`, or by whitespace if `delim` is empty.
+fn fields<'a>(line: &'a str, delim: &'a str) -> Box<dyn Iterator<Item = &'a str> + 'a> {
+    match delim {
+        "" => Box::new(line.split_whitespace()),
+        _ => Box::new(line.split(delim).map(str::trim)),
+    }
+}
+
+/// Extracts every integer in `line`, ignoring any other text. A `-` directly
+/// before a number is taken as its sign, unless it follows a digit as in
+/// ranges like `2-4`.
+pub fn ints<T: FromStr>(line: &str) -> Result<Vec<T>, ParseError> {
+    let bytes = line.as_bytes();
+    let mut res = vec![];
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let after_digit = i > 0 && bytes[i - 1].is_ascii_digit();
+        if bytes[i] == b'-' && !after_digit && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            i += 1;
+        }
+        res.push(parse_field(line, &line[start..i])?);
+    }
+    Ok(res)
+}
+
+/// Parses every field of `line` split by `delim`, or by whitespace if
+/// `delim` is empty.
+pub fn list<T: FromStr>(line: &str, delim: &str) -> Result<Vec<T>, ParseError> {
+    fields(line, delim).map(|f| parse_field(line, f)).collect()
+}
+
+/// Parses exactly `N` fields of `line` split by `delim`, or by whitespace if
+/// `delim` is empty.
+pub fn tuple<T: FromStr, const N: usize>(line: &str, delim: &str) -> Result<[T; N], ParseError> {
+    let values = list(line, delim)?;
+    let found = values.len();
+
+    values
+        .try_into()
+        .map_err(|_| ParseError::new(1, ParseErrorKind::FieldCount { expected: N, found }))
+}
+
+/// Parses a `a<delim>b` pair.
+pub fn pair<T: FromStr>(line: &str, delim: &str) -> Result<(T, T), ParseError> {
+    tuple(line, delim).map(|[a, b]| (a, b))
+}
+
+/// Parses `N` whitespace separated columns into one list per column.
+pub fn columns<T: FromStr, const N: usize>(input: &str) -> Result<[Vec<T>; N], ParseError> {
+    let mut cols = std::array::from_fn(|_| vec![]);
+
+    parse_lines(input, |l| {
+        let row = tuple::<T, N>(l, "")?;
+        for (col, v) in cols.iter_mut().zip(row) {
+            col.push(v);
+        }
+        Ok(())
+    })?;
+    Ok(cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_ints() {
+        assert_eq!(ints::<i64>("x=-3, y=12..-7 z-4"), Ok(vec![-3, 12, -7, -4]));
+        assert_eq!(ints::<u8>("a 1 b 2 -"), Ok(vec![1, 2]));
+        assert_eq!(ints::<i64>("1-3,-5--6"), Ok(vec![1, 3, -5, -6]));
+        assert_eq!(ints::<u64>("2-4,6-8"), Ok(vec![2, 4, 6, 8]));
+        assert_eq!(
+            ints::<u8>("1 300"),
+            Err(ParseError {
+                line: 1,
+                col: 3,
+                kind: ParseErrorKind::InvalidValue("300".to_owned()),
+            })
+        );
+    }
+
+    #[test]
+    fn test_delimited() {
+        assert_eq!(pair::<u32>("47|53", "|"), Ok((47, 53)));
+        assert_eq!(list::<u32>("75,47,61", ","), Ok(vec![75, 47, 61]));
+        assert_eq!(tuple::<u32, 3>("1  2 3", ""), Ok([1, 2, 3]));
+        assert_eq!(
+            pair::<u32>("47|x", "|").map_err(|e| (e.col, e.kind)),
+            Err((4, ParseErrorKind::InvalidValue("x".to_owned())))
+        );
+        assert_eq!(
+            tuple::<u32, 2>("1 2 3", "").map_err(|e| e.kind),
+            Err(ParseErrorKind::FieldCount {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_paragraphs_and_columns() {
+        let input = indoc! { r#"
+            1   4
+            2   5
+
+            3   x
+        "# };
+
+        let blocks = paragraphs(input).collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].text, "1   4\n2   5");
+        assert_eq!(blocks[1].line, 4);
+
+        assert_eq!(
+            columns::<u32, 2>(blocks[0].text),
+            Ok([vec![1, 2], vec![4, 5]])
+        );
+
+        let err = blocks[1].parse_lines(|l| pair::<u32>(l, "")).unwrap_err();
+        assert_eq!((err.line, err.col), (4, 5));
+        assert_eq!(err.to_string(), r#"line 4, column 5: invalid value "x""#);
+    }
+}
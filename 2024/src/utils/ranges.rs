@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+/// Set of integers stored as sorted, disjoint and non-adjacent half-open
+/// ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    ranges: Vec<Range<i64>>,
+}
+
+/// Parts of a range before, inside and after another one, as returned by
+/// [`split_range`]. Empty parts are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeSplit {
+    pub before: Option<Range<i64>>,
+    pub inside: Option<Range<i64>>,
+    pub after: Option<Range<i64>>,
+}
+
+/// Splits `r` by the bounds of `by`, e.g. to map the part of a range that
+/// falls inside a mapping source and keep the rest as is.
+pub fn split_range(r: &Range<i64>, by: &Range<i64>) -> RangeSplit {
+    let non_empty = |r: Range<i64>| (!r.is_empty()).then_some(r);
+    RangeSplit {
+        before: non_empty(r.start..r.end.min(by.start)),
+        inside: non_empty(r.start.max(by.start)..r.end.min(by.end)),
+        after: non_empty(r.start.max(by.end)..r.end),
+    }
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every value of `r`, merging it with overlapping or adjacent
+    /// ranges.
+    pub fn insert(&mut self, r: Range<i64>) {
+        if r.is_empty() {
+            return;
+        }
+
+        // Ranges touching `r` are contiguous in the sorted list.
+        let lo = self.ranges.partition_point(|s| s.end < r.start);
+        let hi = self.ranges.partition_point(|s| s.start <= r.end);
+
+        let merged = match lo < hi {
+            true => self.ranges[lo].start.min(r.start)..self.ranges[hi - 1].end.max(r.end),
+            false => r,
+        };
+        self.ranges.splice(lo..hi, [merged]);
+    }
+
+    /// Removes every value of `r`, splitting ranges that contain it.
+    pub fn remove(&mut self, r: Range<i64>) {
+        if r.is_empty() {
+            return;
+        }
+
+        let lo = self.ranges.partition_point(|s| s.end <= r.start);
+        let hi = self.ranges.partition_point(|s| s.start < r.end);
+
+        let rest = self.ranges[lo..hi]
+            .iter()
+            .flat_map(|s| {
+                let split = split_range(s, &r);
+                [split.before, split.after]
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+        self.ranges.splice(lo..hi, rest);
+    }
+
+    pub fn contains(&self, x: i64) -> bool {
+        let i = self.ranges.partition_point(|s| s.end <= x);
+        self.ranges.get(i).is_some_and(|s| s.start <= x)
+    }
+
+    /// Whether every value of `r` is in the set.
+    pub fn contains_range(&self, r: &Range<i64>) -> bool {
+        if r.is_empty() {
+            return true;
+        }
+        let i = self.ranges.partition_point(|s| s.end <= r.start);
+        self.ranges
+            .get(i)
+            .is_some_and(|s| s.start <= r.start && r.end <= s.end)
+    }
+
+    /// Whether any value of `r` is in the set.
+    pub fn overlaps(&self, r: &Range<i64>) -> bool {
+        let i = self.ranges.partition_point(|s| s.end <= r.start);
+        !r.is_empty() && self.ranges.get(i).is_some_and(|s| s.start < r.end)
+    }
+
+    /// Number of values in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|r| r.start.abs_diff(r.end)).sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn ranges(&self) -> &[Range<i64>] {
+        &self.ranges
+    }
+
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|r| r.start)
+    }
+
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|r| r.end - 1)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        res.extend(other.ranges.iter().cloned());
+        res
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut ranges = vec![];
+
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let r = a.start.max(b.start)..a.end.min(b.end);
+            if !r.is_empty() {
+                ranges.push(r);
+            }
+            match a.end < b.end {
+                true => i += 1,
+                false => j += 1,
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut res = self.clone();
+        for r in &other.ranges {
+            res.remove(r.clone());
+        }
+        res
+    }
+
+    /// Splits the set into the values inside `by` and the ones outside it.
+    pub fn split_by(&self, by: &Range<i64>) -> (Self, Self) {
+        let by = Self::from_iter([by.clone()]);
+        (self.intersection(&by), self.difference(&by))
+    }
+}
+
+impl FromIterator<Range<i64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = Range<i64>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Range<i64>> for RangeSet {
+    fn extend<I: IntoIterator<Item = Range<i64>>>(&mut self, iter: I) {
+        for r in iter {
+            self.insert(r);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_merge_and_split() {
+        let mut set: RangeSet = [5..8, 0..2, 2..3, 10..12].into_iter().collect();
+        assert_eq!(set.ranges(), [0..3, 5..8, 10..12]);
+        assert_eq!(set.len(), 8);
+
+        set.insert(7..10);
+        assert_eq!(set.ranges(), [0..3, 5..12]);
+
+        set.remove(1..6);
+        assert_eq!(set.ranges(), [0..1, 6..12]);
+        assert!(set.contains(0) && !set.contains(1) && set.contains(11));
+        assert!(set.contains_range(&(7..10)) && !set.contains_range(&(0..7)));
+        assert!(set.overlaps(&(3..7)) && !set.overlaps(&(1..6)));
+
+        let (inside, outside) = set.split_by(&(8..20));
+        assert_eq!(inside.ranges().len(), 1);
+        assert_eq!((inside.min(), inside.max()), (Some(8), Some(11)));
+        assert_eq!(outside.ranges(), [0..1, 6..8]);
+
+        assert_eq!(
+            split_range(&(0..10), &(3..5)),
+            RangeSplit {
+                before: Some(0..3),
+                inside: Some(3..5),
+                after: Some(5..10),
+            }
+        );
+    }
+
+    fn arb_ranges() -> impl Strategy<Value = Vec<Range<i64>>> {
+        prop::collection::vec((-20i64..20, 0i64..8).prop_map(|(s, l)| s..s + l), 0..6)
+    }
+
+    fn to_set(ranges: &[Range<i64>]) -> BTreeSet<i64> {
+        ranges.iter().cloned().flatten().collect()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_set_operations(a in arb_ranges(), b in arb_ranges()) {
+            let (sa, sb) = (to_set(&a), to_set(&b));
+            let (ra, rb): (RangeSet, RangeSet) = (a.into_iter().collect(), b.into_iter().collect());
+
+            prop_assert_eq!(to_set(ra.ranges()), sa.clone());
+            prop_assert!(ra.ranges().windows(2).all(|w| w[0].end < w[1].start));
+            prop_assert_eq!(ra.len(), sa.len() as u64);
+
+            prop_assert_eq!(to_set(ra.union(&rb).ranges()), &sa | &sb);
+            prop_assert_eq!(to_set(ra.intersection(&rb).ranges()), &sa & &sb);
+            prop_assert_eq!(to_set(ra.difference(&rb).ranges()), &sa - &sb);
+            prop_assert!((-25..30).all(|x| ra.contains(x) == sa.contains(&x)));
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Scanning error with the byte offset in the input where it happened.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScanError {
+    pub offset: usize,
+    pub kind: ScanErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScanErrorKind {
+    /// Input doesn't contain the template literal at the offset.
+    Literal(String),
+    /// Capture doesn't parse as the requested type.
+    InvalidValue(String),
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ScanErrorKind::Literal(l) => write!(f, "offset {}: expected {l:?}", self.offset),
+            ScanErrorKind::InvalidValue(v) => {
+                write!(f, "offset {}: invalid value {v:?}", self.offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+/// Matches `input` against `template`, where every `{}` is a capture and the
+/// rest must match literally. Each capture extends up to the first occurrence
+/// of the literal that follows it, the last literal must end the input.
+///
+/// Returns the captures along with their byte offsets in `input`.
+pub fn captures<'a>(template: &str, input: &'a str) -> Result<Vec<(usize, &'a str)>, ScanError> {
+    let mut literals = template.split("{}");
+    let first = literals.next().unwrap_or_default();
+    let literals = literals.collect::<Vec<_>>();
+    assert!(
+        literals[..literals.len().saturating_sub(1)]
+            .iter()
+            .all(|l| !l.is_empty()),
+        "template {template:?} has adjacent captures"
+    );
+
+    let literal_error = |offset, l: &str| ScanError {
+        offset,
+        kind: ScanErrorKind::Literal(l.to_owned()),
+    };
+
+    let mut rest = input
+        .strip_prefix(first)
+        .ok_or_else(|| literal_error(0, first))?;
+    let mut caps = Vec::with_capacity(literals.len());
+
+    for (k, lit) in literals.iter().enumerate() {
+        let offset = input.len() - rest.len();
+        let end = match k == literals.len() - 1 {
+            true => rest.strip_suffix(lit).map(str::len),
+            false => rest.find(lit),
+        }
+        .ok_or_else(|| literal_error(offset, lit))?;
+
+        caps.push((offset, &rest[..end]));
+        rest = &rest[end + lit.len()..];
+    }
+
+    match rest.is_empty() {
+        true => Ok(caps),
+        false => Err(literal_error(input.len() - rest.len(), "")),
+    }
+}
+
+#[doc(hidden)]
+pub fn parse_capture<T: FromStr>((offset, cap): (usize, &str)) -> Result<T, ScanError> {
+    cap.parse().map_err(|_| ScanError {
+        offset,
+        kind: ScanErrorKind::InvalidValue(cap.to_owned()),
+    })
+}
+
+/// Like [`scan!`], but returns a `Result` with the location of the mismatch.
+#[macro_export]
+macro_rules! try_scan {
+    ($template:literal, $input:expr => $t:ty $(,)?) => {
+        $crate::try_scan!(@tuple $template, $input => $t).map(|(v,)| v)
+    };
+    ($template:literal, $input:expr => $($t:ty),+ $(,)?) => {
+        $crate::try_scan!(@tuple $template, $input => $($t),+)
+    };
+    (@tuple $template:literal, $input:expr => $($t:ty),+) => {
+        (|| -> Result<($($t,)+), $crate::utils::scan::ScanError> {
+            let caps = $crate::utils::scan::captures($template, $input)?;
+            let types = [$(stringify!($t)),+];
+            assert_eq!(
+                caps.len(),
+                types.len(),
+                "template {:?} captures don't match the types {:?}",
+                $template,
+                types
+            );
+
+            let mut caps = caps.into_iter();
+            Ok(($($crate::utils::scan::parse_capture::<$t>(caps.next().unwrap())?,)+))
+        })()
+    };
+}
+
+/// Matches a line against a template with typed `{}` captures, e.g.
+/// `scan!("mul({},{})", line => i64, i64)` returns `Option<(i64, i64)>`.
+/// A single type returns the value itself instead of a 1-tuple.
+#[macro_export]
+macro_rules! scan {
+    ($template:literal, $input:expr => $($t:ty),+ $(,)?) => {
+        $crate::try_scan!($template, $input => $($t),+).ok()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan() {
+        assert_eq!(scan!("mul({},{})", "mul(2,4)" => i64, i64), Some((2, 4)));
+        assert_eq!(scan!("{}|{}", "47|53" => u32, u32), Some((47, 53)));
+        assert_eq!(
+            scan!("#{} @ {}", "#12 @ left" => u8, String),
+            Some((12, "left".to_owned()))
+        );
+        assert_eq!(scan!("x={}", "x=-7" => i32), Some(-7));
+        assert_eq!(scan!("mul({},{})", "mul(2,4]" => i64, i64), None);
+    }
+
+    #[test]
+    fn test_try_scan_errors() {
+        assert_eq!(
+            try_scan!("mul({},{})", "mul(2,4]" => i64, i64),
+            Err(ScanError {
+                offset: 6,
+                kind: ScanErrorKind::Literal(")".to_owned())
+            })
+        );
+        assert_eq!(
+            try_scan!("mul({},{})", "mul(2,x)" => i64, i64),
+            Err(ScanError {
+                offset: 6,
+                kind: ScanErrorKind::InvalidValue("x".to_owned())
+            })
+        );
+        assert_eq!(
+            try_scan!("mul({},{})", "mux(2,4)" => i64, i64).map_err(|e| e.offset),
+            Err(0)
+        );
+    }
+}
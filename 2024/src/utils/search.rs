@@ -0,0 +1,301 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use super::{geometry::Point, grid::Grid};
+
+/// Breadth-first search from `start` until `goal` returns true, returning the
+/// path including both ends. Its length minus one is the distance.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::<N, Option<N>>::from([(start.clone(), None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if goal(&node) {
+            let mut path = vec![node];
+            while let Some(Some(p)) = parents.get(path.last().unwrap()) {
+                path.push(p.clone());
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for next in neighbors(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Distance from `start` to every reachable node, in number of edges.
+pub fn bfs_distances<N, I>(start: N, mut neighbors: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Hash + Eq + Clone,
+    I: IntoIterator<Item = N>,
+{
+    let mut dist = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        let d = dist[&node];
+        for next in neighbors(&node) {
+            if !dist.contains_key(&next) {
+                dist.insert(next.clone(), d + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    dist
+}
+
+/// Every shortest path between two nodes, as found by [`all_shortest_paths`].
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<N, C> {
+    pub cost: C,
+    nodes: Vec<N>,
+    preds: Vec<Vec<usize>>,
+    goal: usize,
+}
+
+impl<N: Hash + Eq + Clone, C> ShortestPaths<N, C> {
+    /// Nodes that are part of at least one shortest path.
+    pub fn nodes(&self) -> HashSet<N> {
+        let mut seen = HashSet::from([self.goal]);
+        let mut stack = vec![self.goal];
+        while let Some(i) = stack.pop() {
+            stack.extend(self.preds[i].iter().filter(|&&p| seen.insert(p)));
+        }
+        seen.into_iter().map(|i| self.nodes[i].clone()).collect()
+    }
+
+    /// Every shortest path, from start to goal. Their number can grow
+    /// exponentially, prefer [`ShortestPaths::nodes`] when possible.
+    pub fn paths(&self) -> Vec<Vec<N>> {
+        let mut paths = vec![];
+        let mut stack = vec![vec![self.goal]];
+
+        while let Some(path) = stack.pop() {
+            let last = *path.last().unwrap();
+            if self.preds[last].is_empty() {
+                paths.push(path.iter().rev().map(|&i| self.nodes[i].clone()).collect());
+                continue;
+            }
+            for &p in &self.preds[last] {
+                let mut next = path.clone();
+                next.push(p);
+                stack.push(next);
+            }
+        }
+        paths
+    }
+}
+
+/// Best-first search shared by Dijkstra and A*. Nodes are indexed on first
+/// sight so they don't need to be `Ord`.
+fn best_first<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut goal: impl FnMut(&N) -> bool,
+) -> Option<ShortestPaths<N, C>>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut index = HashMap::from([(start.clone(), 0)]);
+    let mut nodes = vec![start];
+    let mut dist = vec![C::default()];
+    let mut preds = vec![vec![]];
+    let mut done = vec![false];
+
+    let mut heap = BinaryHeap::from([Reverse((heuristic(&nodes[0]), 0))]);
+
+    while let Some(Reverse((_, i))) = heap.pop() {
+        if std::mem::replace(&mut done[i], true) {
+            continue;
+        }
+        if goal(&nodes[i]) {
+            return Some(ShortestPaths {
+                cost: dist[i],
+                nodes,
+                preds,
+                goal: i,
+            });
+        }
+
+        for (next, cost) in neighbors(&nodes[i]) {
+            let d = dist[i] + cost;
+            let j = *index.entry(next).or_insert_with_key(|next| {
+                nodes.push(next.clone());
+                dist.push(d);
+                preds.push(vec![]);
+                done.push(false);
+                nodes.len() - 1
+            });
+
+            if preds[j].is_empty() && j != 0 || d < dist[j] {
+                dist[j] = d;
+                preds[j] = vec![i];
+                heap.push(Reverse((d + heuristic(&nodes[j]), j)));
+            } else if d == dist[j] && j != 0 && !preds[j].contains(&i) {
+                preds[j].push(i);
+            }
+        }
+    }
+    None
+}
+
+fn first_path<N: Clone, C>(res: &ShortestPaths<N, C>) -> Vec<N> {
+    let mut path = vec![res.goal];
+    while let Some(&p) = res.preds[*path.last().unwrap()].first() {
+        path.push(p);
+    }
+    path.iter().rev().map(|&i| res.nodes[i].clone()).collect()
+}
+
+/// Cheapest path from `start` to a node where `goal` returns true, with the
+/// cost of each edge given by `neighbors`. Returns the path and its cost.
+pub fn dijkstra<N, C, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, neighbors, |_| C::default(), goal)
+}
+
+/// Like [`dijkstra`], guided by a `heuristic` that must never overestimate
+/// the remaining cost to the goal.
+pub fn astar<N, C, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> C,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(start, neighbors, heuristic, goal).map(|res| (first_path(&res), res.cost))
+}
+
+/// Like [`dijkstra`], but keeps every path tied for the lowest cost.
+pub fn all_shortest_paths<N, C, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    goal: impl FnMut(&N) -> bool,
+) -> Option<ShortestPaths<N, C>>
+where
+    N: Hash + Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    best_first(start, neighbors, |_| C::default(), goal)
+}
+
+/// Shortest path between two cells moving orthogonally through cells where
+/// `passable` returns true.
+pub fn grid_bfs<T>(
+    grid: &Grid<T>,
+    start: Point,
+    goal: Point,
+    passable: impl Fn(&T) -> bool,
+) -> Option<Vec<Point>> {
+    bfs(
+        start,
+        |&p| grid.neighbors4(p).filter(|&n| passable(&grid[n])),
+        |&p| p == goal,
+    )
+}
+
+/// Cheapest path between two cells moving orthogonally, where `cost` gives
+/// the cost of entering a cell or `None` if it's a wall. Uses the Manhattan
+/// distance as the A* heuristic, so every step must cost at least one.
+pub fn grid_astar<T>(
+    grid: &Grid<T>,
+    start: Point,
+    goal: Point,
+    cost: impl Fn(&T) -> Option<usize>,
+) -> Option<(Vec<Point>, usize)> {
+    astar(
+        start,
+        |&p| {
+            grid.neighbors4(p)
+                .filter_map(|n| cost(&grid[n]).map(|c| (n, c)))
+                .collect::<Vec<_>>()
+        },
+        |&p| p.manhattan(goal),
+        |&p| p == goal,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const MAZE: &str = indoc! { r#"
+        S..#....
+        .#.#.##.
+        .#...#..
+        ..#.#...
+        #.....#E
+    "# };
+
+    #[test]
+    fn test_grid_search() {
+        let grid: Grid<char> = MAZE.parse().unwrap();
+        let (start, goal) = (grid.find(&'S').unwrap(), grid.find(&'E').unwrap());
+
+        let path = grid_bfs(&grid, start, goal, |&c| c != '#').unwrap();
+        assert_eq!(path.len() - 1, 13);
+        assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+
+        let (_, cost) = grid_astar(&grid, start, goal, |&c| (c != '#').then_some(1)).unwrap();
+        assert_eq!(cost, 13);
+        assert_eq!(grid_bfs(&grid, start, Point(0, 3), |&c| c != '#'), None);
+    }
+
+    #[test]
+    fn test_weighted() {
+        // 0 -> 1 -> 3 and 0 -> 2 -> 3 both cost 4, 0 -> 3 costs 5.
+        let edges = |&n: &u32| match n {
+            0 => vec![(1, 1), (2, 3), (3, 5)],
+            1 => vec![(3, 3)],
+            2 => vec![(3, 1)],
+            _ => vec![],
+        };
+
+        assert_eq!(dijkstra(0, edges, |&n| n == 3), Some((vec![0, 1, 3], 4)));
+
+        let all = all_shortest_paths(0, edges, |&n| n == 3).unwrap();
+        let mut paths = all.paths();
+        paths.sort();
+        assert_eq!(paths, vec![vec![0, 1, 3], vec![0, 2, 3]]);
+        assert_eq!(all.nodes(), HashSet::from([0, 1, 2, 3]));
+        assert_eq!(
+            bfs_distances(0, |&n| edges(&n).into_iter().map(|e| e.0))[&3],
+            1
+        );
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    io::{self, Stdout, Write},
+    process,
+};
+
+const SHOW_CURSOR: &str = "\x1b[?25h";
+
+/// Terminal used for animations, redrawn in place with ANSI escape codes.
+/// The screen is cleared on creation and the cursor hidden until dropped, or
+/// until the program is interrupted with Ctrl-C.
+pub struct Screen {
+    out: Stdout,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        // Drop doesn't run when the process is killed by SIGINT. Setting the
+        // handler fails if a previous screen already did, which is fine.
+        let _ = ctrlc::set_handler(|| {
+            let mut out = io::stdout();
+            let _ = writeln!(out, "{SHOW_CURSOR}");
+            let _ = out.flush();
+            process::exit(130);
+        });
+
+        let mut out = io::stdout();
+        // Clear the screen and hide the cursor.
+        let _ = write!(out, "\x1b[2J\x1b[?25l");
+        Self { out }
+    }
+
+    /// Replaces the screen contents with `frame`.
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        // Go home, clear the leftovers of each line then the rest of the
+        // screen, so a shorter frame doesn't keep parts of the previous one.
+        write!(self.out, "\x1b[H{}\x1b[J", frame.replace('\n', "\x1b[K\n"))?;
+        self.out.flush()
+    }
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let _ = write!(self.out, "{SHOW_CURSOR}");
+        let _ = self.out.flush();
+    }
+}